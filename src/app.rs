@@ -1,9 +1,20 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
-
-use crate::screen::{self, Session};
-use crate::workspace::{self, TreeNode};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::archive::{self, Archive};
+use crate::config::{self, Config, RepoConfig, WorkspaceRoot};
+use crate::hooks::{self, HookEvent, HookSet, HookTarget};
+use crate::logging;
+use crate::monitor::{self, Finished, Monitor, MonitorEvent};
+use crate::paths;
+use crate::query::{Query, Subject};
+use crate::report;
+use crate::screen::{self, Session, WindowInfo};
+use crate::scrollback::{self, CachedScrollback, ContentMatch, ScrollbackCache};
+use crate::state::{self, SavedSession};
+use crate::workspace::{self, ScanOptions, TreeNode};
 
 #[derive(PartialEq)]
 pub enum Mode {
@@ -19,6 +30,17 @@ pub enum Mode {
     Ordering,
     ConstantOrdering,
     EditingCommand,
+    EditingTags,
+    CreatingWorktree,
+    ConfirmRemoveWorktree,
+    ConfirmRecreate,
+    ConfirmTrust,
+    RestorePreview,
+    Archives,
+    ArchiveView,
+    ContentSearch,
+    ContentResults,
+    FinishResult,
 }
 
 pub enum Action {
@@ -28,11 +50,48 @@ pub enum Action {
     Quit,
 }
 
+/// A session about to be created in a repo whose `.scrn.toml` runs
+/// commands the user hasn't trusted yet.
+pub struct TrustPrompt {
+    pub name: String,
+    dir: Option<PathBuf>,
+    pub repo: PathBuf,
+    pub config: RepoConfig,
+    hash: u64,
+}
+
 /// Data collected by a refresh — can be built on a background thread.
 pub struct RefreshData {
     pub sessions: Vec<Session>,
     pub session_has_proc: HashMap<u32, bool>,
     pub workspace_tree: Option<TreeNode>,
+    /// pid_name -> window snapshot (titles, cwds, commands)
+    pub session_windows: HashMap<String, Vec<WindowInfo>>,
+    /// False for timer ticks: no tree scan, and windows only for sessions
+    /// that weren't known yet
+    pub full: bool,
+}
+
+/// What the selected row is, independent of where it sits in the list.
+#[derive(Clone, Debug, PartialEq)]
+enum SelectionKey {
+    /// pid_name, or the name of a constant that isn't running
+    Session(String),
+    /// a repo or directory in the workspace tree
+    Path(PathBuf),
+}
+
+/// One session handled by a background restore.
+pub struct RestoreProgress {
+    pub name: String,
+    pub result: Result<(), screen::Failure>,
+}
+
+/// One session archived and killed by a background kill.
+pub struct KillProgress {
+    pub name: String,
+    pub pid_name: String,
+    pub result: Result<(), String>,
 }
 
 #[derive(Clone)]
@@ -53,6 +112,13 @@ pub enum ListItem {
         path: PathBuf,
         session: Option<Session>,
         prefix: String,
+        /// display label and colour from the repo's `.scrn.toml`
+        label: Option<String>,
+        color: Option<(u8, u8, u8)>,
+        /// root marker the scan matched, for the row icon
+        marker: Option<String>,
+        /// for a linked worktree row, the main repo it belongs to
+        worktree_of: Option<PathBuf>,
     },
 }
 
@@ -62,20 +128,54 @@ pub struct App {
     pub selected: usize,
     pub mode: Mode,
     pub search_input: String,
+    /// last successfully parsed `search_input`
+    pub search_query: Query,
+    /// parse error for the current `search_input`, shown in the search bar
+    pub search_error: Option<String>,
     pub create_input: String,
     pub cursor_pos: usize,
     pub status_msg: String,
     pub status_set_at: Instant,
     pub current_session: Option<String>,
     pub action: Action,
-    pub workspace_dir: Option<PathBuf>,
+    pub workspace_roots: Vec<WorkspaceRoot>,
+    pub scan_options: ScanOptions,
     pub workspace_tree: Option<TreeNode>,
+    /// `workspace_tree` came from the on-disk cache and hasn't been
+    /// rescanned yet
+    tree_from_cache: bool,
+    /// Repo names held by more than one repo in the tree. Their sessions
+    /// can't be matched to a repo, so they're listed as plain sessions.
+    clashing_repos: HashSet<String>,
+    /// set until the first background refresh lands, for the loading spinner
+    pub loading_since: Option<Instant>,
+    refresh_rx: Option<Receiver<RefreshData>>,
+    /// background refresh period while the picker is idle; `None` is off
+    refresh_interval: Option<Duration>,
+    last_refresh: Instant,
+    restore_rx: Option<Receiver<RestoreProgress>>,
+    /// (handled, succeeded, total) for the restore in progress
+    restore_progress: (usize, usize, usize),
+    kill_rx: Option<Receiver<KillProgress>>,
+    /// (killed, failed, total) for the kill in progress
+    kill_progress: (usize, usize, usize),
+    /// Worktree to remove once the kill in progress succeeds:
+    /// (session name, worktree path, main repo path)
+    kill_then_remove: Option<(String, PathBuf, PathBuf)>,
     pub display_items: Vec<ListItem>,
     pub selectable_indices: Vec<usize>,
     pub pin_target: Option<String>,
     pub constant_target: Option<String>,
     pub kill_session_info: Option<(String, String)>,
-    pub pre_search_selected: usize,
+    /// (repo name, repo path) a new worktree is being created for
+    pub worktree_repo: Option<(String, PathBuf)>,
+    /// (session name, worktree path, main repo path) awaiting removal
+    pub worktree_remove: Option<(String, PathBuf, PathBuf)>,
+    /// Repo session that vanished before it could be attached, and its repo
+    pub recreate_target: Option<(String, PathBuf)>,
+    pub trust_prompt: Option<TrustPrompt>,
+    /// Selection anchor from before the search, restored by identity on Esc
+    pre_search_anchor: Vec<SelectionKey>,
     pub search_filter_active: bool,
     /// screen PID -> whether a foreground process is running in the session
     pub session_has_proc: HashMap<u32, bool>,
@@ -90,11 +190,16 @@ pub struct App {
     pub table_data_end_y: u16,
     pub table_scroll_offset: usize,
     pub last_click: Option<(Instant, usize)>,
-    pub dir_order: Vec<String>,
+    /// workspace root name -> top-level directory order within that root
+    pub dir_order: HashMap<String, Vec<String>>,
     pub ordering_items: Vec<String>,
+    /// root whose directories the ordering modal is arranging
+    pub ordering_root: String,
     pub ordering_selected: usize,
     /// constant name -> command to run when opened
     pub constant_commands: HashMap<String, String>,
+    /// session/repo name -> tags, matched by `tag:` search terms
+    pub tags: HashMap<String, Vec<String>>,
     /// absolute paths of folded tree directories
     pub folded_dirs: HashSet<String>,
     /// previously-attached session name (for jump-to-last / backtick)
@@ -102,64 +207,140 @@ pub struct App {
     /// most-recently-attached session name (shifts into last_attached on next attach)
     pub current_attached: Option<String>,
     /// sessions to restore on startup, loaded before refresh_sessions overwrites the file
-    sessions_to_restore: Vec<(String, Option<PathBuf>)>,
+    sessions_to_restore: Vec<SavedSession>,
+    /// pid_name -> window snapshot from the last refresh
+    pub session_windows: HashMap<String, Vec<WindowInfo>>,
+    /// re-run foreground commands when restoring
+    pub restore_commands: bool,
+    /// restore-preview modal: saved session + whether it is ticked
+    pub restore_candidates: Vec<(SavedSession, bool)>,
+    pub restore_selected: usize,
+    /// scrollback archives, newest first (archive browser)
+    pub archives: Vec<Archive>,
+    pub archive_selected: usize,
+    /// contents of the archive being viewed, and its scroll offset
+    pub archive_lines: Vec<String>,
+    pub archive_scroll: usize,
+    /// full-text scrollback search: last query, its hits and the cursor
+    pub content_query: String,
+    pub content_results: Vec<ContentMatch>,
+    pub content_selected: usize,
+    scrollback_cache: ScrollbackCache,
+    /// Scrollback being captured for the content search in progress, and
+    /// the sessions it searches
+    content_rx: Option<Receiver<Vec<(String, CachedScrollback)>>>,
+    content_sessions: Vec<Session>,
+    /// watched sessions: activity badges, bell and silence events
+    pub monitor: Monitor,
+    notify: bool,
+    notify_hook: Option<String>,
+    /// status line is drawn highlighted until this instant
+    pub status_flash_until: Option<Instant>,
+    /// commands waited on with `n` that have exited: (name, pid_name, result),
+    /// shown one at a time
+    pub finished_results: Vec<(String, String, Finished)>,
+    /// lifecycle hook commands from config
+    hooks: Vec<HookSet>,
 }
 
 impl App {
-    pub fn new(workspace: Option<PathBuf>) -> Self {
+    pub fn new(cfg: &Config) -> Self {
+        state::migrate(&cfg.workspaces);
+        let saved = state::load();
         Self {
             sessions: Vec::new(),
             all_sessions: Vec::new(),
             selected: 0,
             mode: Mode::Normal,
             search_input: String::new(),
+            search_query: Query::default(),
+            search_error: None,
             create_input: String::new(),
             cursor_pos: 0,
             status_msg: String::new(),
             status_set_at: Instant::now(),
             current_session: std::env::var("STY").ok(),
             action: Action::None,
-            workspace_dir: workspace,
+            workspace_roots: cfg.workspaces.clone(),
+            scan_options: cfg.scan.clone(),
             workspace_tree: None,
+            tree_from_cache: false,
+            clashing_repos: HashSet::new(),
+            loading_since: None,
+            refresh_rx: None,
+            refresh_interval: Some(Duration::from_secs(cfg.refresh_interval)).filter(|d| !d.is_zero()),
+            last_refresh: Instant::now(),
+            restore_rx: None,
+            restore_progress: (0, 0, 0),
+            kill_rx: None,
+            kill_progress: (0, 0, 0),
+            kill_then_remove: None,
             display_items: Vec::new(),
             selectable_indices: Vec::new(),
             pin_target: None,
             constant_target: None,
             kill_session_info: None,
-            pre_search_selected: 0,
+            worktree_repo: None,
+            worktree_remove: None,
+            recreate_target: None,
+            trust_prompt: None,
+            pre_search_anchor: Vec::new(),
             search_filter_active: true,
             session_has_proc: HashMap::new(),
-            history: load_history(),
+            history: saved.history,
             filter_opened: false,
-            pins: load_pins(),
-            constants: load_constants(),
+            pins: saved.pins,
+            constants: saved.constants,
             table_data_y: 0,
             table_data_end_y: 0,
             table_scroll_offset: 0,
             last_click: None,
-            dir_order: load_dir_order(),
+            dir_order: saved.dir_order,
             ordering_items: Vec::new(),
+            ordering_root: String::new(),
             ordering_selected: 0,
-            constant_commands: load_constant_commands(),
-            folded_dirs: load_folded_dirs(),
+            constant_commands: saved.constant_commands,
+            tags: saved.tags,
+            folded_dirs: saved.folded_dirs,
             last_attached: None,
             current_attached: None,
-            sessions_to_restore: load_saved_sessions(),
+            sessions_to_restore: saved.sessions,
+            session_windows: HashMap::new(),
+            restore_commands: cfg.restore_commands,
+            restore_candidates: Vec::new(),
+            restore_selected: 0,
+            archives: Vec::new(),
+            archive_selected: 0,
+            archive_lines: Vec::new(),
+            archive_scroll: 0,
+            content_query: String::new(),
+            content_results: Vec::new(),
+            content_selected: 0,
+            scrollback_cache: ScrollbackCache::default(),
+            content_rx: None,
+            content_sessions: Vec::new(),
+            monitor: Monitor::new(cfg.silence_secs),
+            notify: cfg.notify,
+            notify_hook: cfg.notify_hook.clone(),
+            status_flash_until: None,
+            finished_results: Vec::new(),
+            hooks: cfg.hooks.clone(),
         }
     }
 
     pub fn refresh_sessions(&mut self) {
-        let dir = self.workspace_dir.clone();
+        let roots = self.workspace_roots.clone();
+        let scan_options = self.scan_options.clone();
         let dir_order = self.dir_order.clone();
+        // A cached tree is rescanned in the background instead
+        let scan = !self.tree_from_cache;
         let (sessions, process_map, workspace_tree) = std::thread::scope(|s| {
-            let sessions_h = s.spawn(|| screen::list_sessions());
-            let ps_h = s.spawn(|| screen::build_process_map());
+            let sessions_h = s.spawn(screen::list_sessions);
+            let ps_h = s.spawn(screen::build_process_map);
             let tree_h = s.spawn(move || {
-                dir.as_ref().map(|d| {
-                    let mut tree = workspace::scan_tree(d);
-                    if !dir_order.is_empty() {
-                        reorder_tree_children(&mut tree, &dir_order);
-                    }
+                let roots = if scan { roots } else { Vec::new() };
+                workspace::scan_roots(&roots, &scan_options).map(|mut tree| {
+                    apply_dir_order(&mut tree, &roots, &dir_order);
                     tree
                 })
             });
@@ -177,66 +358,429 @@ impl App {
             .filter_map(|s| s.pid_name.split('.').next()?.parse().ok())
             .collect();
         self.session_has_proc = screen::has_foreground_from_map(&process_map, &pids);
-        if workspace_tree.is_some() {
-            self.workspace_tree = workspace_tree;
+        self.session_windows = screen::snapshot_sessions(&process_map, &self.all_sessions);
+        if let Some(tree) = workspace_tree {
+            self.set_tree(tree);
+        }
+        save_sessions(&self.all_sessions, &self.session_windows, &self.workspace_tree);
+        self.monitor.retain(&self.all_sessions);
+        self.apply_search_filter();
+    }
+
+    /// Draw the first frame from cached state and gather the rest in the
+    /// background: the tree from the last scan (when it was made with the
+    /// current roots and scan options) and a full refresh. The restore
+    /// preview opens once live sessions are known.
+    pub fn start_loading(&mut self) {
+        if let Some(mut tree) = workspace::load_cache(&self.workspace_roots, &self.scan_options) {
+            apply_dir_order(&mut tree, &self.workspace_roots, &self.dir_order);
+            self.workspace_tree = Some(tree);
+            self.tree_from_cache = true;
+            self.check_repo_clashes();
         }
-        save_sessions(&self.all_sessions, &self.workspace_tree);
         self.apply_search_filter();
+        self.loading_since = Some(Instant::now());
+        self.start_refresh();
+    }
+
+    /// Refresh sessions, processes and the tree in the background. A
+    /// refresh already running is dropped, since it may predate a change.
+    pub fn start_refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        self.refresh_rx = Some(spawn_refresh(
+            self.workspace_roots.clone(),
+            self.scan_options.clone(),
+            self.dir_order.clone(),
+            None,
+        ));
+    }
+
+    /// The timer's cheaper refresh: sessions and processes only, with
+    /// window queries just for sessions that weren't there before.
+    fn start_light_refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        let known = self.session_windows.keys().cloned().collect();
+        self.refresh_rx = Some(spawn_refresh(
+            self.workspace_roots.clone(),
+            self.scan_options.clone(),
+            self.dir_order.clone(),
+            Some(known),
+        ));
+    }
+
+    /// Start a background refresh when `refresh_interval` has passed. Only
+    /// while browsing, so rows don't move under a modal.
+    pub fn tick_auto_refresh(&mut self) {
+        let Some(interval) = self.refresh_interval else {
+            return;
+        };
+        if self.refresh_rx.is_none()
+            && self.restore_rx.is_none()
+            && self.kill_rx.is_none()
+            && matches!(self.mode, Mode::Normal | Mode::Searching)
+            && self.last_refresh.elapsed() >= interval
+        {
+            self.start_light_refresh();
+        }
+    }
+
+    /// Apply a background refresh if one has finished.
+    pub fn poll_refresh(&mut self) {
+        let Some(rx) = &self.refresh_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(data) => {
+                self.refresh_rx = None;
+                self.apply_refresh_data(data);
+                self.loading_since = None;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.refresh_rx = None;
+                self.loading_since = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+        if self.loading_since.is_none() && self.mode == Mode::Normal && !self.sessions_to_restore.is_empty() {
+            self.start_restore_preview();
+        }
+    }
+
+    /// Take a freshly scanned tree, updating the cache when it changed.
+    fn set_tree(&mut self, tree: TreeNode) {
+        self.tree_from_cache = false;
+        if self.workspace_tree.as_ref() != Some(&tree) {
+            workspace::save_cache(&self.workspace_roots, &self.scan_options, &tree);
+            self.workspace_tree = Some(tree);
+            self.check_repo_clashes();
+        }
     }
 
-    /// Apply a completed background refresh to app state.
-    pub fn apply_refresh_data(&mut self, data: RefreshData) {
+    /// Find repo names shared by several repos and say so when the set
+    /// changes. Sessions are named after repos, so scrn can't tell which
+    /// repo such a session belongs to and won't guess.
+    fn check_repo_clashes(&mut self) {
+        let clashes = repo_name_clashes(self.workspace_tree.as_ref());
+        let names: HashSet<String> = clashes.iter().map(|(name, _)| name.clone()).collect();
+        if names == self.clashing_repos {
+            return;
+        }
+        self.clashing_repos = names;
+        if clashes.is_empty() {
+            return;
+        }
+        let list: Vec<String> = clashes
+            .iter()
+            .map(|(name, paths)| {
+                let dirs: Vec<String> = paths
+                    .iter()
+                    .map(|p| self.workspace_relative(p).unwrap_or_else(|| p.display().to_string()))
+                    .collect();
+                format!("'{name}' ({})", dirs.join(", "))
+            })
+            .collect();
+        let msg = format!("Error: repos share a name and get no session until renamed: {}", list.join("; "));
+        self.set_status(msg);
+    }
+
+    /// Apply a completed background refresh to app state. A timer tick
+    /// that found the same sessions in the same states changes nothing; one
+    /// that didn't keeps the window snapshots of sessions it didn't query,
+    /// and only saves the session list when its membership changed.
+    pub fn apply_refresh_data(&mut self, mut data: RefreshData) {
+        let same_set = session_keys(&self.all_sessions).eq(session_keys(&data.sessions));
+        if !data.full {
+            let same_states = self.all_sessions.iter().zip(&data.sessions).all(|(a, b)| a.state == b.state);
+            if same_set && same_states && self.session_has_proc == data.session_has_proc {
+                return;
+            }
+            for s in &data.sessions {
+                if let Some(windows) = self.session_windows.get(&s.pid_name) {
+                    data.session_windows.entry(s.pid_name.clone()).or_insert_with(|| windows.clone());
+                }
+            }
+        }
         self.all_sessions = data.sessions;
         self.session_has_proc = data.session_has_proc;
-        if data.workspace_tree.is_some() {
-            self.workspace_tree = data.workspace_tree;
+        self.session_windows = data.session_windows;
+        if let Some(tree) = data.workspace_tree {
+            self.set_tree(tree);
+        }
+        if data.full || !same_set {
+            save_sessions(&self.all_sessions, &self.session_windows, &self.workspace_tree);
         }
-        save_sessions(&self.all_sessions, &self.workspace_tree);
+        self.monitor.retain(&self.all_sessions);
         self.apply_search_filter();
     }
 
-    pub fn restore_sessions(&mut self) {
+    /// Keys to try re-selecting after a rebuild: the selected row, then the
+    /// rows around it in the same section, nearest first and below before
+    /// above.
+    fn selection_anchor(&self) -> Vec<SelectionKey> {
+        let Some(&selected_idx) = self.selectable_indices.get(self.selected) else {
+            return Vec::new();
+        };
+        // Section number of every display row; headers and separators split
+        let mut section = 0;
+        let sections: Vec<usize> = self
+            .display_items
+            .iter()
+            .map(|item| {
+                if matches!(item, ListItem::SectionHeader(_) | ListItem::Separator) {
+                    section += 1;
+                }
+                section
+            })
+            .collect();
+        let same: Vec<usize> = self
+            .selectable_indices
+            .iter()
+            .copied()
+            .filter(|&idx| sections[idx] == sections[selected_idx])
+            .collect();
+        let pos = same.iter().position(|&idx| idx == selected_idx).unwrap_or(0);
+        let mut order = vec![pos];
+        for d in 1..same.len() {
+            if pos + d < same.len() {
+                order.push(pos + d);
+            }
+            if d <= pos {
+                order.push(pos - d);
+            }
+        }
+        order
+            .into_iter()
+            .filter_map(|p| item_key(&self.display_items[same[p]]))
+            .collect()
+    }
+
+    /// Select the first row of `anchor` that is still listed.
+    fn restore_selection(&mut self, anchor: &[SelectionKey]) {
+        let keys: Vec<Option<SelectionKey>> = self
+            .selectable_indices
+            .iter()
+            .map(|&idx| self.display_items.get(idx).and_then(item_key))
+            .collect();
+        let found = anchor
+            .iter()
+            .find_map(|key| keys.iter().position(|k| k.as_ref() == Some(key)));
+        if let Some(pos) = found {
+            self.selected = pos;
+        }
+    }
+
+    /// Offer the saved sessions that are not already running in the
+    /// restore-preview modal. Everything starts ticked.
+    pub fn start_restore_preview(&mut self) {
         let saved = std::mem::take(&mut self.sessions_to_restore);
-        if saved.is_empty() {
-            return;
+        let live_names: HashSet<&str> =
+            self.all_sessions.iter().map(|s| s.name.as_str()).collect();
+        self.restore_candidates = saved
+            .into_iter()
+            .filter(|s| !live_names.contains(s.name.as_str()))
+            .map(|s| (s, true))
+            .collect();
+        if !self.restore_candidates.is_empty() {
+            self.restore_selected = 0;
+            self.mode = Mode::RestorePreview;
         }
+    }
+
+    pub fn toggle_restore_candidate(&mut self) {
+        if let Some((_, ticked)) = self.restore_candidates.get_mut(self.restore_selected) {
+            *ticked = !*ticked;
+        }
+    }
+
+    pub fn toggle_all_restore_candidates(&mut self) {
+        let all = self.restore_candidates.iter().all(|(_, t)| *t);
+        for (_, ticked) in &mut self.restore_candidates {
+            *ticked = !all;
+        }
+    }
+
+    pub fn cancel_restore(&mut self) {
+        self.restore_candidates.clear();
+        self.mode = Mode::Normal;
+    }
 
-        let live_names: std::collections::HashSet<String> =
-            self.all_sessions.iter().map(|s| s.name.clone()).collect();
+    /// Rebuild the ticked sessions on a background thread; progress is
+    /// picked up by `poll_restore`.
+    pub fn restore_sessions(&mut self) {
+        let candidates = std::mem::take(&mut self.restore_candidates);
+        self.mode = Mode::Normal;
 
-        let mut restored = 0;
-        for (name, path) in &saved {
-            if live_names.contains(name) {
-                continue;
+        let ticked: Vec<SavedSession> = candidates
+            .into_iter()
+            .filter(|(_, ticked)| *ticked)
+            .map(|(saved, _)| saved)
+            .collect();
+        if ticked.is_empty() {
+            return;
+        }
+        self.restore_progress = (0, 0, ticked.len());
+        self.set_status(format!("Restoring sessions 0/{}", ticked.len()));
+        let restore_commands = self.restore_commands;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for saved in ticked {
+                let result = screen::restore_session(
+                    &saved.name,
+                    saved.path.as_deref(),
+                    &saved.windows,
+                    restore_commands,
+                );
+                if tx.send(RestoreProgress { name: saved.name, result }).is_err() {
+                    return;
+                }
             }
-            let result = if let Some(dir) = path {
-                screen::create_session_in_dir(name, dir)
-            } else {
-                screen::create_session(name)
-            };
-            match result {
-                Ok(()) => restored += 1,
-                Err(e) => crate::logging::log_error(&format!("Failed to restore session '{name}': {e}")),
+        });
+        self.restore_rx = Some(rx);
+    }
+
+    /// Report restore progress in the status bar and refresh when done.
+    pub fn poll_restore(&mut self) {
+        let Some(rx) = &self.restore_rx else {
+            return;
+        };
+        let mut finished = false;
+        let mut progress = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(p) => progress.push(p),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
             }
         }
-
-        if restored > 0 {
-            self.refresh_sessions();
+        if progress.is_empty() && !finished {
+            return;
+        }
+        for p in progress {
+            self.restore_progress.0 += 1;
+            match p.result {
+                Ok(()) => {
+                    self.run_hooks(HookEvent::Create, &p.name, "");
+                    self.restore_progress.1 += 1;
+                }
+                Err(e) => logging::error(&format!("Failed to restore session '{}': {e}", p.name)),
+            }
+        }
+        let (done, restored, total) = self.restore_progress;
+        if !finished && done < total {
+            self.set_status(format!("Restoring sessions {done}/{total}"));
+            return;
+        }
+        self.restore_rx = None;
+        let failed = total - restored;
+        if failed > 0 {
+            self.set_status(format!("Failed to restore {failed} of {total} sessions, see the log"));
+        } else {
             self.set_status(format!(
                 "Restored {restored} session{}",
                 if restored == 1 { "" } else { "s" }
             ));
         }
+        if restored > 0 {
+            self.start_refresh();
+        }
     }
 
     pub fn set_status(&mut self, msg: String) {
         if msg.starts_with("Error") || msg.starts_with("Failed") {
-            crate::logging::log_error(&msg);
+            logging::error(&msg);
         }
         self.status_msg = msg;
         self.status_set_at = Instant::now();
     }
 
+    /// Set a status message that is drawn highlighted for a moment.
+    fn flash_status(&mut self, msg: String) {
+        self.set_status(msg);
+        self.status_flash_until = Some(Instant::now() + std::time::Duration::from_secs(2));
+    }
+
+    /// Start or stop watching the selected session for activity, bells and
+    /// silence.
+    pub fn toggle_watch(&mut self) {
+        let Some(session) = self.selected_session().cloned() else {
+            return;
+        };
+        match self.monitor.toggle(&session.pid_name) {
+            Ok(true) => self.set_status(format!("Watching '{}'", session.name)),
+            Ok(false) => self.set_status(format!("Stopped watching '{}'", session.name)),
+            Err(e) => self.set_status(format!("Error: couldn't change watching of '{}': {e}", session.name)),
+        }
+    }
+
+    /// Wait for the selected session's foreground command to exit, then
+    /// notify and show its last lines of output.
+    pub fn toggle_finish_watch(&mut self) {
+        let Some(session) = self.selected_session().cloned() else {
+            return;
+        };
+        match self.monitor.toggle_finish(&session.pid_name) {
+            Ok(Some(command)) => {
+                self.set_status(format!("Will notify when '{command}' finishes"))
+            }
+            Ok(None) => self.set_status(format!("Stopped waiting on '{}'", session.name)),
+            Err(e) => self.set_status(format!("{e} in '{}'", session.name)),
+        }
+    }
+
+    /// Pick up finished monitor polls and report bell/silence/finish events.
+    pub fn poll_monitor(&mut self) {
+        for (pid_name, event) in self.monitor.tick() {
+            let name = pid_name.split_once('.').map(|(_, n)| n).unwrap_or(&pid_name).to_string();
+            let msg = match &event {
+                MonitorEvent::Bell => format!("'{name}' rang the bell"),
+                MonitorEvent::Silence => format!("'{name}' went quiet"),
+                MonitorEvent::Finished(f) => format!(
+                    "'{}' finished in {} after at least {}",
+                    f.command,
+                    name,
+                    monitor::format_elapsed(f.elapsed)
+                ),
+            };
+            // The last line of output is usually enough to tell pass from fail
+            let body = match &event {
+                MonitorEvent::Finished(f) => match f.tail.last() {
+                    Some(last) => format!("{msg}\n{}", last.trim()),
+                    None => msg.clone(),
+                },
+                _ => msg.clone(),
+            };
+            monitor::notify(self.notify, self.notify_hook.as_deref(), &name, event.as_str(), &body);
+            self.flash_status(msg);
+            if let MonitorEvent::Finished(f) = event {
+                self.finished_results.push((name, pid_name, f));
+            }
+        }
+        if self.mode == Mode::Normal && !self.finished_results.is_empty() {
+            self.mode = Mode::FinishResult;
+        }
+    }
+
+    pub fn close_finish_result(&mut self) {
+        if !self.finished_results.is_empty() {
+            self.finished_results.remove(0);
+        }
+        self.mode = Mode::Normal;
+    }
+
+    pub fn attach_finish_result(&mut self) {
+        if self.finished_results.is_empty() {
+            self.mode = Mode::Normal;
+            return;
+        }
+        let (name, pid_name, _) = self.finished_results.remove(0);
+        self.record_opened(&name);
+        self.mode = Mode::Normal;
+        self.action = Action::Attach(pid_name);
+    }
+
     pub fn move_up(&mut self) {
         if self.selected > 0 {
             self.selected -= 1;
@@ -277,17 +821,20 @@ impl App {
     pub fn confirm_pin(&mut self) {
         if let Some(name) = self.pin_target.take() {
             if self.pins.contains(&name) {
-                self.pins.remove(&name);
+                self.pins = state::update(|state| {
+                    state.pins.remove(&name);
+                })
+                .pins;
                 self.set_status(format!("Unpinned '{name}'"));
             } else {
-                self.pins.insert(name.clone());
-                if let Some(pos) = self.constants.iter().position(|n| n == &name) {
-                    self.constants.remove(pos);
-                    save_constants(&self.constants);
-                }
+                let state = state::update(|state| {
+                    state.pins.insert(name.clone());
+                    state.constants.retain(|n| n != &name);
+                });
+                self.pins = state.pins;
+                self.constants = state.constants;
                 self.set_status(format!("Pinned '{name}'"));
             }
-            save_pins(&self.pins);
             self.rebuild_display_list();
         }
         self.mode = Mode::Normal;
@@ -310,17 +857,20 @@ impl App {
 
     pub fn confirm_constant(&mut self) {
         if let Some(name) = self.constant_target.take() {
-            if let Some(pos) = self.constants.iter().position(|n| n == &name) {
-                self.constants.remove(pos);
+            if self.constants.contains(&name) {
+                self.constants = state::update(|state| state.constants.retain(|n| n != &name)).constants;
                 self.set_status(format!("Removed from constants '{name}'"));
             } else {
-                self.constants.push(name.clone());
-                if self.pins.remove(&name) {
-                    save_pins(&self.pins);
-                }
+                let state = state::update(|state| {
+                    if !state.constants.contains(&name) {
+                        state.constants.push(name.clone());
+                    }
+                    state.pins.remove(&name);
+                });
+                self.pins = state.pins;
+                self.constants = state.constants;
                 self.set_status(format!("Added to constants '{name}'"));
             }
-            save_constants(&self.constants);
             self.rebuild_display_list();
         }
         self.mode = Mode::Normal;
@@ -334,12 +884,22 @@ impl App {
     pub fn start_search(&mut self) {
         self.mode = Mode::Searching;
         self.search_input.clear();
-        self.pre_search_selected = self.selected;
+        self.pre_search_anchor = self.selection_anchor();
         self.search_filter_active = true;
+        self.search_error = None;
     }
 
     pub fn apply_search_filter(&mut self) {
-        let filter_active = self.search_filter_active && !self.search_input.is_empty();
+        match Query::parse(&self.search_input) {
+            Ok(query) => {
+                self.search_query = query;
+                self.search_error = None;
+            }
+            // Keep filtering by the last valid query while the input is mid-edit
+            Err(e) => self.search_error = Some(e),
+        }
+        let filter_active = self.search_filter_active && !self.search_query.is_empty();
+        let repo_dirs = if filter_active { self.repo_dirs() } else { HashMap::new() };
         self.sessions = self
             .all_sessions
             .iter()
@@ -353,8 +913,8 @@ impl App {
                 if !filter_active {
                     true
                 } else {
-                    let haystack = format!("{} {}", s.name, s.pid_name);
-                    fuzzy_match(&haystack, &self.search_input).is_some()
+                    let dir = repo_dirs.get(&s.name).cloned().or_else(|| self.session_cwd(s));
+                    self.query_matches(&s.name, Some(s), dir.as_deref())
                 }
             })
             .cloned()
@@ -362,19 +922,158 @@ impl App {
         self.rebuild_display_list();
     }
 
+    /// Whether a row matches the current search query.
+    fn query_matches(&self, name: &str, session: Option<&Session>, dir: Option<&str>) -> bool {
+        let busy = session
+            .and_then(|s| s.pid_name.split('.').next()?.parse::<u32>().ok())
+            .and_then(|pid| self.session_has_proc.get(&pid).copied())
+            .unwrap_or(false);
+        self.search_query.matches(&Subject {
+            name,
+            session,
+            busy,
+            pinned: self.pins.contains(name),
+            constant: self.constants.iter().any(|c| c == name),
+            tags: self.tags.get(name).map(Vec::as_slice).unwrap_or(&[]),
+            dir,
+        })
+    }
+
+    /// Workspace-relative path of every repo in the tree, keyed by name,
+    /// leaving out names several repos share.
+    fn repo_dirs(&self) -> HashMap<String, String> {
+        repo_paths(self.workspace_tree.as_ref())
+            .into_iter()
+            .filter_map(|(name, path)| Some((name, self.workspace_relative(&path)?)))
+            .collect()
+    }
+
+    /// Working directory of a session's first window, for `dir:` terms on
+    /// sessions outside the workspace tree.
+    fn session_cwd(&self, session: &Session) -> Option<String> {
+        self.session_windows
+            .get(&session.pid_name)?
+            .iter()
+            .find_map(|w| w.cwd.as_ref())
+            .map(|c| c.display().to_string())
+    }
+
+    pub fn start_content_search(&mut self) {
+        self.create_input = self.content_query.clone();
+        self.cursor_pos = self.create_input.chars().count();
+        self.mode = Mode::ContentSearch;
+    }
+
+    /// Capture the scrollback of every session whose cached copy is stale
+    /// on a background thread; `poll_content_search` lists the lines that
+    /// contain the query once it's in.
+    pub fn confirm_content_search(&mut self) {
+        let query = self.create_input.trim().to_string();
+        if query.is_empty() {
+            self.cancel_content_search();
+            return;
+        }
+        self.content_sessions = self
+            .all_sessions
+            .iter()
+            .filter(|s| !self.is_current_session(s))
+            .cloned()
+            .collect();
+        let stale = self.scrollback_cache.stale(&self.content_sessions);
+        self.content_query = query;
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+        if stale.is_empty() {
+            self.show_content_results();
+            return;
+        }
+        self.set_status(format!("Searching the scrollback of {} sessions\u{2026}", stale.len()));
+        let (tx, rx) = mpsc::sync_channel(1);
+        std::thread::spawn(move || {
+            let _ = tx.send(scrollback::capture_all(stale));
+        });
+        self.content_rx = Some(rx);
+    }
+
+    /// Take in the captured scrollback and show the matches.
+    pub fn poll_content_search(&mut self) {
+        let Some(rx) = &self.content_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(captured) => {
+                self.content_rx = None;
+                self.scrollback_cache.insert(captured);
+                self.show_content_results();
+            }
+            Err(mpsc::TryRecvError::Disconnected) => self.content_rx = None,
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+
+    fn show_content_results(&mut self) {
+        let sessions = std::mem::take(&mut self.content_sessions);
+        self.content_results = self.scrollback_cache.search(&sessions, &self.content_query);
+        self.content_selected = 0;
+        if self.content_results.is_empty() {
+            self.set_status(format!("No output matches '{}'", self.content_query));
+        } else if self.mode != Mode::Normal {
+            // Don't pull the results over whatever was opened meanwhile
+            self.set_status(format!(
+                "{} lines match '{}', search again to list them",
+                self.content_results.len(),
+                self.content_query
+            ));
+        } else {
+            self.set_status(String::new());
+            self.mode = Mode::ContentResults;
+        }
+    }
+
+    pub fn cancel_content_search(&mut self) {
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn close_content_results(&mut self) {
+        self.mode = Mode::Normal;
+    }
+
+    /// Attach to the session of the selected hit, on the window it came from.
+    pub fn attach_content_result(&mut self) {
+        let Some(m) = self.content_results.get(self.content_selected).cloned() else {
+            return;
+        };
+        if let Err(e) = screen::send(&m.pid_name, &["select", &m.window.to_string()]) {
+            self.set_status(format!("Error: couldn't select window {} in '{}': {e}", m.window, m.name));
+            return;
+        }
+        self.record_opened(&m.name);
+        self.mode = Mode::Normal;
+        self.action = Action::Attach(m.pid_name);
+    }
+
     pub fn toggle_search_filter(&mut self) {
         self.search_filter_active = !self.search_filter_active;
         self.apply_search_filter();
     }
 
     fn rebuild_display_list(&mut self) {
+        let anchor = self.selection_anchor();
         self.display_items.clear();
         self.selectable_indices.clear();
 
         // Clone sessions to avoid borrow conflicts
         let sessions_clone: Vec<Session> = self.sessions.clone();
-        let session_map: std::collections::HashMap<&str, &Session> =
-            sessions_clone.iter().map(|s| (s.name.as_str(), s)).collect();
+        // A session named after repos that share the name stays a plain
+        // session, so it isn't shown under whichever repo came first
+        let session_map: std::collections::HashMap<&str, &Session> = sessions_clone
+            .iter()
+            .filter(|s| !self.clashing_repos.contains(&s.name))
+            .map(|s| (s.name.as_str(), s))
+            .collect();
 
         let mut merged_sessions: std::collections::HashSet<String> =
             std::collections::HashSet::new();
@@ -383,7 +1082,21 @@ impl App {
         let mut ws_items: Vec<ListItem> = Vec::new();
         let mut ws_selectable: Vec<usize> = Vec::new();
 
-        let filter_active = self.search_filter_active && !self.search_input.is_empty();
+        let filter_active = self.search_filter_active && !self.search_query.is_empty();
+
+        // Tree repos are matched against every live session, not just the
+        // ones that survived the filter, so `-is:attached` can't strip a
+        // session from its repo and keep the repo
+        let all_session_map: HashMap<&str, &Session> = self
+            .all_sessions
+            .iter()
+            .filter(|s| !self.clashing_repos.contains(&s.name))
+            .map(|s| (s.name.as_str(), s))
+            .collect();
+        let repo_matches = |node: &TreeNode| {
+            let dir = self.workspace_relative(&node.path);
+            self.query_matches(&node.name, all_session_map.get(node.name.as_str()).copied(), dir.as_deref())
+        };
 
         if let Some(ref tree) = self.workspace_tree {
             let tree = tree.clone();
@@ -399,11 +1112,10 @@ impl App {
                     &self.folded_dirs,
                 );
             } else {
-                let query = self.search_input.clone();
                 flatten_filtered(
                     &tree,
                     0,
-                    &query,
+                    &repo_matches,
                     &session_map,
                     &mut merged_sessions,
                     &mut ws_items,
@@ -422,12 +1134,8 @@ impl App {
         // Sort by fuzzy match score when searching
         if filter_active {
             all_orphan_sessions.sort_by(|a, b| {
-                let score_a = fuzzy_match(&a.name, &self.search_input)
-                    .map(|(_, s)| s)
-                    .unwrap_or(i32::MIN);
-                let score_b = fuzzy_match(&b.name, &self.search_input)
-                    .map(|(_, s)| s)
-                    .unwrap_or(i32::MIN);
+                let score_a = self.search_query.score(&a.name);
+                let score_b = self.search_query.score(&b.name);
                 score_b.cmp(&score_a)
             });
         }
@@ -486,20 +1194,20 @@ impl App {
             let mut ws_remove: HashSet<usize> = HashSet::new();
             let mut orphan_remove: HashSet<usize> = HashSet::new();
 
-            let mut ws_by_name: HashMap<String, (usize, ListItem, Option<(usize, ListItem)>)> = HashMap::new();
+            type WsEntry = (usize, ListItem, Option<(usize, ListItem)>);
+            let mut ws_by_name: HashMap<String, WsEntry> = HashMap::new();
             let mut last_dir_idx: Option<usize> = None;
             for (i, item) in ws_items.iter().enumerate() {
                 match item {
                     ListItem::TreeDir { .. } | ListItem::SectionHeader(_) => {
                         last_dir_idx = Some(i);
                     }
-                    ListItem::TreeRepo { name, .. } => {
-                        if name_set.contains(name.as_str()) {
+                    ListItem::TreeRepo { name, .. }
+                        if name_set.contains(name.as_str()) => {
                             ws_remove.insert(i);
                             let dir = last_dir_idx.map(|di| (di, ws_items[di].clone()));
                             ws_by_name.insert(name.clone(), (i, item.clone(), dir));
                         }
-                    }
                     _ => {}
                 }
             }
@@ -649,15 +1357,15 @@ impl App {
         let orphans_first = if filter_active {
             let best_ws_score = ws_items.iter().filter_map(|item| {
                 if let ListItem::TreeRepo { name, .. } = item {
-                    fuzzy_match(name, &self.search_input).map(|(_, s)| s)
+                    Some(self.search_query.score(name))
                 } else {
                     None
                 }
             }).max().unwrap_or(i32::MIN);
 
-            let best_orphan_score = all_orphan_sessions.iter().filter_map(|s| {
-                fuzzy_match(&s.name, &self.search_input).map(|(_, sc)| sc)
-            }).max().unwrap_or(i32::MIN);
+            let best_orphan_score = all_orphan_sessions.iter()
+                .map(|s| self.search_query.score(&s.name))
+                .max().unwrap_or(i32::MIN);
 
             best_orphan_score > best_ws_score
         } else {
@@ -743,7 +1451,7 @@ impl App {
         if self.selected >= self.selectable_indices.len() {
             self.selected = self.selectable_indices.len().saturating_sub(1);
         }
-
+        self.restore_selection(&anchor);
     }
 
     pub fn confirm_search(&mut self) {
@@ -754,8 +1462,8 @@ impl App {
         self.search_input.clear();
         self.search_filter_active = true;
         self.apply_search_filter();
-        self.selected = self.pre_search_selected
-            .min(self.selectable_indices.len().saturating_sub(1));
+        let anchor = std::mem::take(&mut self.pre_search_anchor);
+        self.restore_selection(&anchor);
         self.mode = Mode::Normal;
     }
 
@@ -765,6 +1473,15 @@ impl App {
             .and_then(|&idx| self.display_items.get(idx))
     }
 
+    /// The running session behind the selected row, if any.
+    pub fn selected_session(&self) -> Option<&Session> {
+        match self.selected_display_item()? {
+            ListItem::SessionItem(s) if !s.pid_name.is_empty() => Some(s),
+            ListItem::TreeRepo { session, .. } => session.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Set the action to attach or create based on the currently selected item.
     pub fn select_for_attach(&mut self) {
         let item = match self.selected_display_item() {
@@ -775,7 +1492,7 @@ impl App {
             ListItem::SessionItem(session) => {
                 self.record_opened(&session.name);
                 if session.pid_name.is_empty() {
-                    self.action = Action::Create(session.name, None);
+                    self.create(session.name, None);
                 } else {
                     self.action = Action::Attach(session.pid_name);
                 }
@@ -784,9 +1501,12 @@ impl App {
                 if let Some(session) = session {
                     self.record_opened(&session.name);
                     self.action = Action::Attach(session.pid_name);
+                } else if self.clashing_repos.contains(&name) {
+                    self.set_status(format!(
+                        "Error: another repo is also named '{name}', rename one to give it a session"
+                    ));
                 } else {
-                    self.record_opened(&name);
-                    self.action = Action::Create(name, Some(path));
+                    self.create(name, Some(path));
                 }
             }
             ListItem::TreeDir { path, folded, .. } => {
@@ -826,20 +1546,21 @@ impl App {
 
     pub fn create_throwaway(&mut self) {
         let name = generate_throwaway_name(&self.all_sessions);
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
         self.record_opened(&name);
-        self.action = Action::Create(name, Some(PathBuf::from(home)));
+        self.action = Action::Create(name, Some(paths::home_dir()));
     }
 
 
-    pub fn toggle_fold_dir(&mut self, path: &PathBuf, fold: bool) {
+    pub fn toggle_fold_dir(&mut self, path: &Path, fold: bool) {
         let key = path.display().to_string();
-        if fold {
-            self.folded_dirs.insert(key);
-        } else {
-            self.folded_dirs.remove(&key);
-        }
-        save_folded_dirs(&self.folded_dirs);
+        self.folded_dirs = state::update(|state| {
+            if fold {
+                state.folded_dirs.insert(key);
+            } else {
+                state.folded_dirs.remove(&key);
+            }
+        })
+        .folded_dirs;
         self.rebuild_display_list();
     }
 
@@ -857,17 +1578,16 @@ impl App {
         if let Some(ref tree) = self.workspace_tree {
             let mut to_fold: Vec<PathBuf> = Vec::new();
             collect_dir_paths(tree, &mut to_fold);
-            for p in to_fold {
-                self.folded_dirs.insert(p.display().to_string());
-            }
-            save_folded_dirs(&self.folded_dirs);
+            self.folded_dirs = state::update(|state| {
+                state.folded_dirs.extend(to_fold.iter().map(|p| p.display().to_string()));
+            })
+            .folded_dirs;
             self.rebuild_display_list();
         }
     }
 
     pub fn unfold_all(&mut self) {
-        self.folded_dirs.clear();
-        save_folded_dirs(&self.folded_dirs);
+        self.folded_dirs = state::update(|state| state.folded_dirs.clear()).folded_dirs;
         self.rebuild_display_list();
     }
 
@@ -887,31 +1607,74 @@ impl App {
         }
     }
 
+    /// Order the top-level directories of the root holding the selection
+    /// (or the first root that has any).
     pub fn start_ordering(&mut self) {
-        if let Some(ref tree) = self.workspace_tree {
-            let dirs: Vec<String> = tree.children.iter()
+        let selected_path = match self.selected_display_item() {
+            Some(ListItem::TreeDir { path, .. } | ListItem::TreeRepo { path, .. }) => Some(path.clone()),
+            _ => None,
+        };
+        let roots = self.root_nodes();
+        let has_dirs = |node: &TreeNode| node.children.iter().any(|c| !c.is_repo);
+        let target = roots
+            .iter()
+            .find(|(_, node)| selected_path.as_ref().is_some_and(|p| p.starts_with(&node.path)) && has_dirs(node))
+            .or_else(|| roots.iter().find(|(_, node)| has_dirs(node)));
+        let Some((root, items)) = target.map(|(root, node)| {
+            let dirs: Vec<String> = node.children.iter()
                 .filter(|c| !c.is_repo)
                 .map(|c| c.name.clone())
                 .collect();
-            if dirs.is_empty() {
-                return;
-            }
-            self.ordering_items = dirs;
-            self.ordering_selected = 0;
-            self.mode = Mode::Ordering;
-        }
+            (root.to_string(), dirs)
+        }) else {
+            return;
+        };
+        self.ordering_items = items;
+        self.ordering_root = root;
+        self.ordering_selected = 0;
+        self.mode = Mode::Ordering;
     }
 
     pub fn confirm_ordering(&mut self) {
-        self.dir_order = self.ordering_items.clone();
-        save_dir_order(&self.dir_order);
+        let (root, items) = (self.ordering_root.clone(), self.ordering_items.clone());
+        self.dir_order = state::update(|state| {
+            state.dir_order.insert(root, items);
+        })
+        .dir_order;
         if let Some(ref mut tree) = self.workspace_tree {
-            reorder_tree_children(tree, &self.dir_order);
+            apply_dir_order(tree, &self.workspace_roots, &self.dir_order);
         }
         self.rebuild_display_list();
         self.mode = Mode::Normal;
     }
 
+    /// The tree node of each workspace root, with the root's name.
+    pub fn root_nodes(&self) -> Vec<(&str, &TreeNode)> {
+        let Some(tree) = &self.workspace_tree else {
+            return Vec::new();
+        };
+        match self.workspace_roots.as_slice() {
+            [root] => vec![(root.name.as_str(), tree)],
+            _ => tree.children.iter().map(|c| (c.name.as_str(), c)).collect(),
+        }
+    }
+
+    /// `path` relative to the workspace root containing it, prefixed with
+    /// the root's name when there are several roots.
+    fn workspace_relative(&self, path: &Path) -> Option<String> {
+        let root = self
+            .workspace_roots
+            .iter()
+            .filter(|r| path.starts_with(&r.path))
+            .max_by_key(|r| r.path.components().count())?;
+        let rel = path.strip_prefix(&root.path).ok()?.display().to_string();
+        Some(match (self.workspace_roots.len(), rel.is_empty()) {
+            (1, _) => rel,
+            (_, true) => root.name.clone(),
+            _ => format!("{}/{rel}", root.name),
+        })
+    }
+
     pub fn cancel_ordering(&mut self) {
         self.mode = Mode::Normal;
     }
@@ -926,8 +1689,15 @@ impl App {
     }
 
     pub fn confirm_constant_ordering(&mut self) {
-        self.constants = self.ordering_items.clone();
-        save_constants(&self.constants);
+        let order = self.ordering_items.clone();
+        self.constants = state::update(|state| {
+            // Keep what another instance added or removed meanwhile
+            let constants = &mut state.constants;
+            let added: Vec<String> = constants.iter().filter(|n| !order.contains(n)).cloned().collect();
+            let kept: Vec<String> = order.into_iter().filter(|n| constants.contains(n)).collect();
+            *constants = kept.into_iter().chain(added).collect();
+        })
+        .constants;
         self.rebuild_display_list();
         self.mode = Mode::Normal;
     }
@@ -936,6 +1706,41 @@ impl App {
         self.mode = Mode::Normal;
     }
 
+    /// The workspace repo at `dir`, or without a directory, the repo a
+    /// session is named after when no other repo shares the name.
+    fn session_repo(&self, name: &str, dir: Option<&Path>) -> Option<PathBuf> {
+        fn find<'a>(node: &'a TreeNode, is_it: &dyn Fn(&TreeNode) -> bool) -> Option<&'a TreeNode> {
+            // A repo's children are its worktrees
+            node.children.iter().find_map(|child| {
+                if child.is_repo && is_it(child) {
+                    Some(child)
+                } else {
+                    find(child, is_it)
+                }
+            })
+        }
+        let tree = self.workspace_tree.as_ref()?;
+        let repo = match dir {
+            Some(dir) => find(tree, &|node| node.path == dir),
+            None if self.clashing_repos.contains(name) => None,
+            None => find(tree, &|node| node.name == name),
+        };
+        repo.map(|node| node.path.clone())
+    }
+
+    /// `.scrn.toml` of the repo a new session is created in, read afresh.
+    /// One that runs commands is only returned while it's trusted as it
+    /// reads now, so an edit after trusting needs trusting again.
+    pub fn repo_config(&self, name: &str, dir: Option<&Path>) -> Option<RepoConfig> {
+        let repo = self.session_repo(name, dir)?;
+        let (config, hash) = config::load_repo_config_hashed(&repo)?;
+        if config.runs_commands() && !is_trusted(&repo, hash) {
+            logging::warn(&format!("Not running untrusted {}", repo.join(".scrn.toml").display()));
+            return None;
+        }
+        Some(config)
+    }
+
     pub fn constant_command(&self, session_name: &str) -> Option<&str> {
         if self.constants.contains(&session_name.to_string()) {
             self.constant_commands.get(session_name).map(|s| s.as_str())
@@ -966,12 +1771,15 @@ impl App {
 
     pub fn confirm_command(&mut self) {
         if let Some(name) = self.selected_item_name() {
-            if self.create_input.is_empty() {
-                self.constant_commands.remove(&name);
-            } else {
-                self.constant_commands.insert(name, self.create_input.clone());
-            }
-            save_constant_commands(&self.constant_commands);
+            let command = self.create_input.clone();
+            self.constant_commands = state::update(|state| {
+                if command.is_empty() {
+                    state.constant_commands.remove(&name);
+                } else {
+                    state.constant_commands.insert(name, command);
+                }
+            })
+            .constant_commands;
         }
         self.create_input.clear();
         self.cursor_pos = 0;
@@ -984,18 +1792,57 @@ impl App {
         self.mode = Mode::Normal;
     }
 
-    pub fn kill_all_throwaway(&mut self) {
-        let throwaway: Vec<String> = self.all_sessions
-            .iter()
-            .filter(|s| s.name.starts_with("tmp-"))
-            .map(|s| s.pid_name.clone())
-            .collect();
-        for pid_name in throwaway {
-            let _ = screen::kill_session(&pid_name);
+    pub fn start_tag_edit(&mut self) {
+        if let Some(name) = self.selected_item_name() {
+            self.create_input = self.tags.get(&name).map(|t| t.join(" ")).unwrap_or_default();
+            self.cursor_pos = self.create_input.chars().count();
+            self.mode = Mode::EditingTags;
         }
     }
 
-    pub fn start_kill(&mut self) {
+    pub fn confirm_tags(&mut self) {
+        if let Some(name) = self.selected_item_name() {
+            let mut seen = HashSet::new();
+            let tags: Vec<String> = self
+                .create_input
+                .split([' ', ','])
+                .map(|t| t.trim_start_matches('#'))
+                .filter(|t| !t.is_empty() && seen.insert(t.to_lowercase()))
+                .map(|t| t.to_string())
+                .collect();
+            self.tags = state::update(|state| {
+                if tags.is_empty() {
+                    state.tags.remove(&name);
+                } else {
+                    state.tags.insert(name, tags);
+                }
+            })
+            .tags;
+            self.apply_search_filter();
+        }
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn cancel_tags(&mut self) {
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn kill_all_throwaway(&mut self) {
+        let throwaway: Vec<String> = self.all_sessions
+            .iter()
+            .filter(|s| s.name.starts_with("tmp-"))
+            .map(|s| s.pid_name.clone())
+            .collect();
+        for pid_name in throwaway {
+            let _ = screen::kill_session(&pid_name);
+        }
+    }
+
+    pub fn start_kill(&mut self) {
         let info = match self.selected_display_item() {
             Some(ListItem::SessionItem(session)) => {
                 Some((session.name.clone(), session.pid_name.clone()))
@@ -1013,15 +1860,7 @@ impl App {
 
     pub fn confirm_kill(&mut self) {
         if let Some((name, pid_name)) = self.kill_session_info.take() {
-            match screen::kill_session(&pid_name) {
-                Ok(()) => {
-                    self.set_status(format!("Killed '{name}'"));
-                    self.refresh_sessions();
-                }
-                Err(e) => {
-                    self.set_status(format!("Error: {e}"));
-                }
-            }
+            self.start_kills(vec![(name, pid_name)]);
         }
         self.mode = Mode::Normal;
     }
@@ -1031,6 +1870,156 @@ impl App {
         self.mode = Mode::Normal;
     }
 
+    /// Ask for a branch to check out in a new worktree of the selected git
+    /// repo (or of the repo a selected worktree belongs to).
+    pub fn start_worktree_create(&mut self) {
+        let repo = match self.selected_display_item() {
+            Some(ListItem::TreeRepo { worktree_of: Some(main), .. }) => main.clone(),
+            Some(ListItem::TreeRepo { path, marker, .. }) if marker.as_deref() == Some(".git") => path.clone(),
+            _ => {
+                self.set_status("Select a git repo to add a worktree".to_string());
+                return;
+            }
+        };
+        let name = repo.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+        self.worktree_repo = Some((name, repo));
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::CreatingWorktree;
+    }
+
+    /// Add the worktree under `<repo>.worktrees/<branch>` and open a session
+    /// in it.
+    pub fn confirm_worktree_create(&mut self) {
+        let branch = self.create_input.trim().to_string();
+        let target = self.worktree_repo.take();
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+        let Some((name, repo)) = target.filter(|_| !branch.is_empty()) else {
+            return;
+        };
+        let path = workspace::worktree_path(&repo, &branch);
+        match workspace::add_worktree(&repo, &branch, &path) {
+            Ok(()) => {
+                let session = workspace::worktree_session_name(&name, &branch);
+                self.create(session, Some(path));
+            }
+            Err(e) => self.set_status(format!("Error: {e}")),
+        }
+    }
+
+    pub fn cancel_worktree_create(&mut self) {
+        self.worktree_repo = None;
+        self.create_input.clear();
+        self.cursor_pos = 0;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn start_worktree_remove(&mut self) {
+        if let Some(ListItem::TreeRepo { name, path, worktree_of: Some(main), .. }) = self.selected_display_item() {
+            self.worktree_remove = Some((name.clone(), path.clone(), main.clone()));
+            self.mode = Mode::ConfirmRemoveWorktree;
+        }
+    }
+
+    /// Kill the worktree's session, if any, then `git worktree remove` it.
+    pub fn confirm_worktree_remove(&mut self) {
+        self.mode = Mode::Normal;
+        let Some((name, path, repo)) = self.worktree_remove.take() else {
+            return;
+        };
+        if let Some(session) = self.all_sessions.iter().find(|s| s.name == name).cloned() {
+            if self.start_kills(vec![(session.name, session.pid_name)]) {
+                self.kill_then_remove = Some((name, path, repo));
+            }
+            return;
+        }
+        self.remove_worktree(&name, &path, &repo);
+    }
+
+    fn remove_worktree(&mut self, name: &str, path: &Path, repo: &Path) {
+        match workspace::remove_worktree(repo, path) {
+            Ok(()) => self.set_status(format!("Removed worktree '{name}'")),
+            Err(e) => self.set_status(format!("Error: {e}")),
+        }
+        self.refresh_sessions();
+    }
+
+    pub fn cancel_worktree_remove(&mut self) {
+        self.worktree_remove = None;
+        self.mode = Mode::Normal;
+    }
+
+    /// Report a failed attach. A repo session that vanished is offered to
+    /// be recreated in its repo.
+    pub fn attach_failed(&mut self, name: &str, failure: screen::Failure) {
+        if let screen::Failure::Gone = failure {
+            if let Some(dir) = repo_paths(self.workspace_tree.as_ref()).remove(name) {
+                self.set_status(format!("Session '{name}' is gone"));
+                self.recreate_target = Some((name.to_string(), dir));
+                self.mode = Mode::ConfirmRecreate;
+                return;
+            }
+        }
+        self.set_status(format!("Error: couldn't attach to '{name}': {failure}"));
+    }
+
+    pub fn confirm_recreate(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some((name, dir)) = self.recreate_target.take() {
+            self.create(name, Some(dir));
+        }
+    }
+
+    /// Create a session, first asking to trust its repo's `.scrn.toml`
+    /// when that runs commands and hasn't been trusted as it reads now.
+    fn create(&mut self, name: String, dir: Option<PathBuf>) {
+        let repo = self.session_repo(&name, dir.as_deref());
+        if let Some((repo, (config, hash))) = repo.and_then(|r| Some((r.clone(), config::load_repo_config_hashed(&r)?))) {
+            if config.runs_commands() && !is_trusted(&repo, hash) {
+                self.trust_prompt = Some(TrustPrompt { name, dir, repo, config, hash });
+                self.mode = Mode::ConfirmTrust;
+                return;
+            }
+        }
+        self.record_opened(&name);
+        self.action = Action::Create(name, dir);
+    }
+
+    /// Trust the prompted `.scrn.toml` as it reads now and create the session.
+    pub fn confirm_trust(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(prompt) = self.trust_prompt.take() {
+            let path = prompt.repo.join(".scrn.toml").display().to_string();
+            state::update(|state| {
+                state.trusted.insert(path, prompt.hash);
+            });
+            self.record_opened(&prompt.name);
+            self.action = Action::Create(prompt.name, prompt.dir);
+        }
+    }
+
+    /// Create the session without running anything from `.scrn.toml`.
+    pub fn skip_trust(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(prompt) = self.trust_prompt.take() {
+            self.set_status(format!("Opened '{}' without its .scrn.toml commands and env", prompt.name));
+            self.record_opened(&prompt.name);
+            self.action = Action::Create(prompt.name, prompt.dir);
+        }
+    }
+
+    pub fn cancel_trust(&mut self) {
+        self.trust_prompt = None;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn cancel_recreate(&mut self) {
+        self.recreate_target = None;
+        self.mode = Mode::Normal;
+    }
+
     pub fn start_kill_all(&mut self) {
         if !self.all_sessions.is_empty() {
             self.mode = Mode::ConfirmKillAll1;
@@ -1042,30 +2031,224 @@ impl App {
     }
 
     pub fn confirm_kill_all_step2(&mut self) {
-        let mut killed = 0;
-        let mut errors = Vec::new();
-        for session in self.all_sessions.clone() {
-            if self.is_current_session(&session) {
-                continue;
+        let targets: Vec<(String, String)> = self
+            .all_sessions
+            .iter()
+            .filter(|s| !self.is_current_session(s))
+            .map(|s| (s.name.clone(), s.pid_name.clone()))
+            .collect();
+        self.start_kills(targets);
+        self.mode = Mode::Normal;
+    }
+
+    pub fn cancel_kill_all(&mut self) {
+        self.mode = Mode::Normal;
+    }
+
+    /// Directory a session lives in: its repo path in the workspace tree,
+    /// else the cwd of its first window.
+    fn session_dir(&self, name: &str, pid_name: &str) -> Option<PathBuf> {
+        repo_paths(self.workspace_tree.as_ref()).remove(name).or_else(|| {
+            self.session_windows
+                .get(pid_name)
+                .and_then(|w| w.first())
+                .and_then(|w| w.cwd.clone())
+        })
+    }
+
+    /// Fire the configured hooks for `event`. `pid_name` may be empty for a
+    /// session that was just created; its pid is then looked up, but only
+    /// when a hook actually applies.
+    pub fn run_hooks(&self, event: HookEvent, name: &str, pid_name: &str) {
+        let pid_of = |pid_name: &str| pid_name.split('.').next()?.parse::<u32>().ok();
+        let repo = self.session_dir(name, pid_name);
+        let rel_path = repo.as_deref().and_then(|r| self.workspace_relative(r));
+        let mut target = HookTarget {
+            session: name,
+            pid: pid_of(pid_name),
+            repo: repo.as_deref(),
+            rel_path: rel_path.as_deref(),
+        };
+        let commands = hooks::commands_for(&self.hooks, event, &target);
+        if commands.is_empty() {
+            return;
+        }
+        if target.pid.is_none() {
+            target.pid = screen::list_sessions()
+                .ok()
+                .and_then(|sessions| sessions.into_iter().find(|s| s.name == name))
+                .and_then(|s| pid_of(&s.pid_name));
+        }
+        hooks::run(&commands, event, &target);
+    }
+
+    /// Archive and kill `(name, pid_name)` sessions on a background thread,
+    /// all at once: dumping the scrollback takes a hardcopy per window.
+    /// Archive failures are logged but never block the kill. Returns false
+    /// when an earlier kill is still running.
+    fn start_kills(&mut self, targets: Vec<(String, String)>) -> bool {
+        if self.kill_rx.is_some() {
+            self.set_status("Error: still killing the last sessions".to_string());
+            return false;
+        }
+        let jobs: Vec<_> = targets
+            .into_iter()
+            .map(|(name, pid_name)| {
+                let cwd = self.session_dir(&name, &pid_name);
+                let windows = self.session_windows.get(&pid_name).cloned().unwrap_or_default();
+                (name, pid_name, cwd, windows)
+            })
+            .collect();
+        self.kill_progress = (0, 0, jobs.len());
+        if let [(name, ..)] = jobs.as_slice() {
+            self.set_status(format!("Killing '{name}'"));
+        }
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            std::thread::scope(|s| {
+                for (name, pid_name, cwd, windows) in jobs {
+                    let tx = tx.clone();
+                    s.spawn(move || {
+                        if let Err(e) = archive::archive_session(&pid_name, &name, cwd.as_deref(), &windows) {
+                            logging::error(&format!("Failed to archive '{name}': {e}"));
+                        }
+                        let result = screen::kill_session(&pid_name);
+                        let _ = tx.send(KillProgress { name, pid_name, result });
+                    });
+                }
+            });
+        });
+        self.kill_rx = Some(rx);
+        true
+    }
+
+    /// Fire the kill hooks of sessions the background kill got to, and
+    /// report and refresh once it's done.
+    pub fn poll_kills(&mut self) {
+        let Some(rx) = &self.kill_rx else {
+            return;
+        };
+        let mut finished = false;
+        let mut progress = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(p) => progress.push(p),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
             }
-            match screen::kill_session(&session.pid_name) {
-                Ok(()) => killed += 1,
-                Err(e) => errors.push(e),
+        }
+        let single = self.kill_progress.2 == 1;
+        for p in progress {
+            match p.result {
+                Ok(()) => {
+                    self.run_hooks(HookEvent::Kill, &p.name, &p.pid_name);
+                    self.kill_progress.0 += 1;
+                    if single {
+                        self.set_status(format!("Killed '{}'", p.name));
+                    }
+                }
+                Err(e) if single => {
+                    self.kill_progress.1 += 1;
+                    self.set_status(format!("Error: {e}"));
+                }
+                Err(e) => {
+                    logging::error(&format!("Failed to kill '{}': {e}", p.name));
+                    self.kill_progress.1 += 1;
+                }
             }
         }
-        if errors.is_empty() {
+        if !finished {
+            return;
+        }
+        self.kill_rx = None;
+        let (killed, failed, total) = self.kill_progress;
+        let worktree = self.kill_then_remove.take();
+        if let Some((name, path, repo)) = worktree.filter(|_| failed == 0) {
+            self.remove_worktree(&name, &path, &repo);
+            return;
+        }
+        // A single kill was reported as it landed
+        if total > 1 && failed == 0 {
             self.set_status(format!("Killed {killed} sessions"));
-        } else {
-            self.set_status(format!("Killed {killed}, {} errors", errors.len()));
+        } else if total > 1 {
+            self.set_status(format!("Killed {killed}, {failed} errors"));
         }
         self.refresh_sessions();
-        self.mode = Mode::Normal;
     }
 
-    pub fn cancel_kill_all(&mut self) {
+    pub fn open_archives(&mut self) {
+        self.archives = archive::list_archives();
+        if self.archives.is_empty() {
+            self.set_status("No archived sessions".to_string());
+            return;
+        }
+        self.archive_selected = 0;
+        self.mode = Mode::Archives;
+    }
+
+    pub fn close_archives(&mut self) {
+        self.archives.clear();
         self.mode = Mode::Normal;
     }
 
+    pub fn view_archive(&mut self) {
+        if let Some(a) = self.archives.get(self.archive_selected) {
+            self.archive_lines = archive::read_archive(a);
+            // Start at the bottom, where the most recent output is
+            self.archive_scroll = self.archive_lines.len().saturating_sub(1);
+            self.mode = Mode::ArchiveView;
+        }
+    }
+
+    pub fn close_archive_view(&mut self) {
+        self.archive_lines.clear();
+        self.mode = Mode::Archives;
+    }
+
+    pub fn delete_archive(&mut self) {
+        if self.archive_selected >= self.archives.len() {
+            return;
+        }
+        let a = self.archives.remove(self.archive_selected);
+        match archive::delete_archive(&a) {
+            Ok(()) => self.set_status(format!("Deleted archive of '{}'", a.name)),
+            Err(e) => self.set_status(format!("Error: {e}")),
+        }
+        if self.archives.is_empty() {
+            self.mode = Mode::Normal;
+        } else if self.archive_selected >= self.archives.len() {
+            self.archive_selected = self.archives.len() - 1;
+        }
+    }
+
+    /// Recreate the selected archive's session and attach to it.
+    pub fn undo_kill(&mut self) {
+        let Some(a) = self.archives.get(self.archive_selected).cloned() else {
+            return;
+        };
+        if self.all_sessions.iter().any(|s| s.name == a.name) {
+            self.set_status(format!("Error: '{}' is already running", a.name));
+            return;
+        }
+        if let Err(e) = archive::undo_kill(&a) {
+            self.set_status(format!("Error: {e}"));
+            return;
+        }
+        self.close_archive_view();
+        self.close_archives();
+        self.refresh_sessions();
+        let session = self.all_sessions.iter().find(|s| s.name == a.name).cloned();
+        // Brought back like any other new session
+        self.run_hooks(HookEvent::Create, &a.name, session.as_ref().map_or("", |s| s.pid_name.as_str()));
+        if let Some(session) = session {
+            self.record_opened(&session.name);
+            self.action = Action::Attach(session.pid_name);
+        }
+    }
+
     pub fn is_current_session(&self, session: &Session) -> bool {
         self.current_session
             .as_ref()
@@ -1077,13 +2260,26 @@ impl App {
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
-        self.history.insert(name.to_string(), ts);
-        save_history(&self.history);
+        self.history = state::update(|state| {
+            state.history.insert(name.to_string(), ts);
+        })
+        .history;
+    }
+
+    /// Record that the user came back to the picker from `name`.
+    pub fn mark_detached(&self, name: &str) {
+        report::log_event("detach", name, &self.event_dir(name));
+    }
+
+    /// Workspace-relative repo path for the events log, empty outside it.
+    fn event_dir(&self, name: &str) -> String {
+        self.repo_dirs().remove(name).unwrap_or_default()
     }
 
     /// Stamp a newly-attached session. `last_attached` holds the session
     /// *before* this one so backtick can jump back to it.
     pub fn mark_attached(&mut self, name: &str) {
+        report::log_event("attach", name, &self.event_dir(name));
         if self.current_attached.as_deref() == Some(name) {
             return;
         }
@@ -1097,7 +2293,7 @@ impl App {
 
 /// Walk down a chain of single-child directory nodes, joining names with `/`.
 /// Returns the collapsed display name and the deepest node whose children should be rendered.
-fn compact_dir_chain<'a>(node: &'a TreeNode) -> (String, &'a TreeNode) {
+fn compact_dir_chain(node: &TreeNode) -> (String, &TreeNode) {
     let mut name = node.name.clone();
     let mut current = node;
     loop {
@@ -1112,6 +2308,7 @@ fn compact_dir_chain<'a>(node: &'a TreeNode) -> (String, &'a TreeNode) {
     (name, current)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn flatten_tree(
     node: &TreeNode,
     depth: usize,
@@ -1155,19 +2352,8 @@ fn flatten_tree(
 
     for child in sorted_children {
         if child.is_repo {
-            let session = session_map.get(child.name.as_str()).cloned().cloned();
-            if let Some(ref s) = session {
-                merged.insert(s.name.clone());
-            }
-
-            let idx = display_items.len();
-            display_items.push(ListItem::TreeRepo {
-                name: child.name.clone(),
-                path: child.path.clone(),
-                session,
-                prefix: " ".to_string(),
-            });
-            selectable_indices.push(idx);
+            let worktrees: Vec<&TreeNode> = child.children.iter().collect();
+            push_repo_rows(child, &worktrees, session_map, merged, display_items, selectable_indices);
         } else {
             let (compact_name, leaf) = compact_dir_chain(child);
             let full_name = if dir_prefix.is_empty() {
@@ -1225,24 +2411,24 @@ fn count_repos(
     (repos, open)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn flatten_filtered(
     node: &TreeNode,
     depth: usize,
-    query: &str,
+    is_match: &dyn Fn(&TreeNode) -> bool,
     session_map: &std::collections::HashMap<&str, &Session>,
     merged: &mut std::collections::HashSet<String>,
     display_items: &mut Vec<ListItem>,
     selectable_indices: &mut Vec<usize>,
     guide_lines: &mut Vec<bool>,
 ) {
-    if !node.is_repo {
-        if !tree_has_match(node, query) {
+    if !node.is_repo
+        && !tree_has_match(node, is_match) {
             return;
         }
-    }
 
     let (source_node, dir_prefix): (&TreeNode, String) = if !node.is_repo && depth == 0 {
-        let has_direct_repos = node.children.iter().any(|c| c.is_repo && fuzzy_match(&c.name, query).is_some());
+        let has_direct_repos = node.children.iter().any(|c| c.is_repo && is_match(c));
         if has_direct_repos {
             let (compact_name, leaf) = compact_dir_chain(node);
             let (descendant_repos, descendant_open) = count_repos(leaf, session_map);
@@ -1269,28 +2455,17 @@ fn flatten_filtered(
         .iter()
         .filter(|child| {
             if child.is_repo {
-                fuzzy_match(&child.name, query).is_some()
+                repo_matches(child, is_match)
             } else {
-                tree_has_match(child, query)
+                tree_has_match(child, is_match)
             }
         })
         .collect();
 
     for child in visible_children.iter() {
         if child.is_repo {
-            let session = session_map.get(child.name.as_str()).cloned().cloned();
-            if let Some(ref s) = session {
-                merged.insert(s.name.clone());
-            }
-
-            let idx = display_items.len();
-            display_items.push(ListItem::TreeRepo {
-                name: child.name.clone(),
-                path: child.path.clone(),
-                session,
-                prefix: " ".to_string(),
-            });
-            selectable_indices.push(idx);
+            let worktrees: Vec<&TreeNode> = child.children.iter().filter(|w| is_match(w)).collect();
+            push_repo_rows(child, &worktrees, session_map, merged, display_items, selectable_indices);
         } else {
             let (compact_name, leaf) = compact_dir_chain(child);
             let full_name = if dir_prefix.is_empty() {
@@ -1310,267 +2485,137 @@ fn flatten_filtered(
             });
             selectable_indices.push(idx);
             guide_lines.push(false);
-            flatten_filtered(leaf, depth + 1, query, session_map, merged, display_items, selectable_indices, guide_lines);
+            flatten_filtered(leaf, depth + 1, is_match, session_map, merged, display_items, selectable_indices, guide_lines);
             guide_lines.pop();
         }
     }
 }
 
-/// Check if any repo descendant of this node matches the query.
-fn tree_has_match(node: &TreeNode, query: &str) -> bool {
-    for child in &node.children {
-        if child.is_repo {
-            if fuzzy_match(&child.name, query).is_some() {
-                return true;
-            }
-        } else if tree_has_match(child, query) {
-            return true;
+fn item_key(item: &ListItem) -> Option<SelectionKey> {
+    match item {
+        ListItem::SessionItem(s) if s.pid_name.is_empty() => Some(SelectionKey::Session(s.name.clone())),
+        ListItem::SessionItem(s) => Some(SelectionKey::Session(s.pid_name.clone())),
+        ListItem::TreeRepo { path, .. } | ListItem::TreeDir { path, .. } => {
+            Some(SelectionKey::Path(path.clone()))
         }
+        ListItem::SectionHeader(_) | ListItem::Separator => None,
     }
-    false
-}
-
-fn pins_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("pins")
-}
-
-fn load_pins() -> HashSet<String> {
-    let path = pins_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return HashSet::new(),
-    };
-    contents
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| l.to_string())
-        .collect()
-}
-
-fn save_pins(pins: &HashSet<String>) {
-    let path = pins_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let mut lines: Vec<&str> = pins.iter().map(|s| s.as_str()).collect();
-    lines.sort();
-    let _ = std::fs::write(&path, lines.join("\n") + "\n");
-}
-
-fn constants_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("constants")
-}
-
-fn load_constants() -> Vec<String> {
-    let path = constants_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
-    contents
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| l.to_string())
-        .collect()
-}
-
-fn save_constants(constants: &[String]) {
-    let path = constants_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let lines: Vec<&str> = constants.iter().map(|s| s.as_str()).collect();
-    let _ = std::fs::write(&path, lines.join("\n") + "\n");
 }
 
-fn constant_commands_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("constant_commands")
+/// A repo matches when it or any of its worktrees does.
+fn repo_matches(repo: &TreeNode, is_match: &dyn Fn(&TreeNode) -> bool) -> bool {
+    is_match(repo) || repo.children.iter().any(is_match)
 }
 
-fn load_constant_commands() -> HashMap<String, String> {
-    let path = constant_commands_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return HashMap::new(),
-    };
-    let mut map = HashMap::new();
-    for line in contents.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-        if let Some((name, cmd)) = line.split_once('=') {
-            let name = name.trim();
-            let cmd = cmd.trim();
-            if !name.is_empty() && !cmd.is_empty() {
-                map.insert(name.to_string(), cmd.to_string());
-            }
-        }
+/// A repo row followed by a row for each of `worktrees`.
+fn push_repo_rows(
+    repo: &TreeNode,
+    worktrees: &[&TreeNode],
+    session_map: &std::collections::HashMap<&str, &Session>,
+    merged: &mut std::collections::HashSet<String>,
+    display_items: &mut Vec<ListItem>,
+    selectable_indices: &mut Vec<usize>,
+) {
+    let rows = std::iter::once((repo, " ", None)).chain(worktrees.iter().enumerate().map(|(i, wt)| {
+        let branch = if i + 1 == worktrees.len() { " \u{2514} " } else { " \u{251c} " };
+        (*wt, branch, Some(repo.path.clone()))
+    }));
+    for (node, prefix, worktree_of) in rows {
+        let session = session_map.get(node.name.as_str()).cloned().cloned();
+        if let Some(ref s) = session {
+            merged.insert(s.name.clone());
+        }
+
+        let idx = display_items.len();
+        display_items.push(ListItem::TreeRepo {
+            name: node.name.clone(),
+            path: node.path.clone(),
+            session,
+            prefix: prefix.to_string(),
+            label: node.repo_config.as_ref().and_then(|c| c.label.clone()),
+            color: node.repo_config.as_ref().and_then(|c| c.color),
+            marker: node.marker.clone(),
+            worktree_of,
+        });
+        selectable_indices.push(idx);
     }
-    map
 }
 
-fn save_constant_commands(commands: &HashMap<String, String>) {
-    let path = constant_commands_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let mut lines: Vec<String> = commands.iter()
-        .map(|(k, v)| format!("{}={}", k, v))
-        .collect();
-    lines.sort();
-    let _ = std::fs::write(&path, lines.join("\n") + "\n");
-}
-
-fn history_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("history")
-}
-
-fn sessions_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("sessions")
-}
-
-
-fn load_history() -> HashMap<String, u64> {
-    let path = history_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return HashMap::new(),
-    };
-    let mut map = HashMap::new();
-    for line in contents.lines() {
-        if let Some((name, ts_str)) = line.split_once('\t') {
-            if let Ok(ts) = ts_str.parse::<u64>() {
-                map.insert(name.to_string(), ts);
+/// Check if any repo descendant of this node matches the query.
+fn tree_has_match(node: &TreeNode, is_match: &dyn Fn(&TreeNode) -> bool) -> bool {
+    for child in &node.children {
+        if child.is_repo {
+            if repo_matches(child, is_match) {
+                return true;
             }
+        } else if tree_has_match(child, is_match) {
+            return true;
         }
     }
-    map
+    false
 }
 
-fn save_history(history: &HashMap<String, u64>) {
-    let path = history_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let mut lines: Vec<String> = history
-        .iter()
-        .map(|(name, ts)| format!("{name}\t{ts}"))
-        .collect();
-    lines.sort();
-    let _ = std::fs::write(&path, lines.join("\n") + "\n");
+/// Whether `repo`'s `.scrn.toml` was trusted with contents hashing to `hash`.
+fn is_trusted(repo: &Path, hash: u64) -> bool {
+    let path = repo.join(".scrn.toml").display().to_string();
+    state::load().trusted.get(&path) == Some(&hash)
 }
 
-fn collect_repo_paths(node: &TreeNode, map: &mut HashMap<String, PathBuf>) {
+fn collect_repo_paths(node: &TreeNode, map: &mut HashMap<String, Vec<PathBuf>>) {
     if node.is_repo {
-        map.insert(node.name.clone(), node.path.clone());
+        map.entry(node.name.clone()).or_default().push(node.path.clone());
     }
     for child in &node.children {
         collect_repo_paths(child, map);
     }
 }
 
-fn save_sessions(all_sessions: &[Session], workspace_tree: &Option<TreeNode>) {
-    let path = sessions_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
+/// Path of every repo in the tree, keyed by name. A name held by several
+/// repos is left out rather than resolved to one of them.
+fn repo_paths(tree: Option<&TreeNode>) -> HashMap<String, PathBuf> {
+    let mut map = HashMap::new();
+    if let Some(tree) = tree {
+        collect_repo_paths(tree, &mut map);
+    }
+    map.into_iter()
+        .filter_map(|(name, mut paths)| (paths.len() == 1).then(|| (name, paths.remove(0))))
+        .collect()
+}
 
-    let mut repo_paths: HashMap<String, PathBuf> = HashMap::new();
-    if let Some(ref tree) = workspace_tree {
-        collect_repo_paths(tree, &mut repo_paths);
+/// Repo names held by more than one repo, with each of their paths.
+fn repo_name_clashes(tree: Option<&TreeNode>) -> Vec<(String, Vec<PathBuf>)> {
+    let mut map = HashMap::new();
+    if let Some(tree) = tree {
+        collect_repo_paths(tree, &mut map);
     }
+    let mut clashes: Vec<(String, Vec<PathBuf>)> = map.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    clashes.sort();
+    clashes
+}
+
+/// Snapshot the running sessions into the state store for the next startup.
+fn save_sessions(
+    all_sessions: &[Session],
+    windows: &HashMap<String, Vec<WindowInfo>>,
+    workspace_tree: &Option<TreeNode>,
+) {
+    let repo_paths = repo_paths(workspace_tree.as_ref());
 
-    let mut lines: Vec<String> = all_sessions
+    let mut snapshot: Vec<SavedSession> = all_sessions
         .iter()
         .filter(|s| !(2..=9).any(|n| s.name.ends_with(&format!("-{n}"))))
         .filter(|s| !s.name.starts_with("tty") && !s.name.starts_with("pts"))
         .filter(|s| !s.name.starts_with("tmp-"))
-        .map(|s| {
-            if let Some(p) = repo_paths.get(&s.name) {
-                format!("{}\t{}", s.name, p.display())
-            } else {
-                s.name.clone()
-            }
+        .map(|s| SavedSession {
+            name: s.name.clone(),
+            path: repo_paths.get(&s.name).cloned(),
+            windows: windows.get(&s.pid_name).cloned().unwrap_or_default(),
         })
         .collect();
-    lines.sort();
-    let _ = std::fs::write(&path, lines.join("\n") + "\n");
-}
-
-fn load_saved_sessions() -> Vec<(String, Option<PathBuf>)> {
-    let path = sessions_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
-    contents
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            if let Some((name, path_str)) = line.split_once('\t') {
-                (name.to_string(), Some(PathBuf::from(path_str)))
-            } else {
-                (line.to_string(), None)
-            }
-        })
-        .collect()
-}
-
-fn dir_order_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".config").join("scrn").join("dir_order")
-}
-
-fn load_dir_order() -> Vec<String> {
-    let path = dir_order_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
-    contents.lines().filter(|l| !l.is_empty()).map(|l| l.to_string()).collect()
-}
-
-fn save_dir_order(order: &[String]) {
-    let path = dir_order_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let _ = std::fs::write(&path, order.join("\n") + "\n");
-}
-
-fn folded_dirs_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".config").join("scrn").join("folded_dirs")
+    snapshot.sort_by(|a, b| a.name.cmp(&b.name));
+    state::update(|state| state.sessions = snapshot);
 }
 
-fn load_folded_dirs() -> HashSet<String> {
-    let path = folded_dirs_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return HashSet::new(),
-    };
-    contents.lines().filter(|l| !l.is_empty()).map(|l| l.to_string()).collect()
-}
-
-fn save_folded_dirs(dirs: &HashSet<String>) {
-    let path = folded_dirs_path();
-    let _ = std::fs::create_dir_all(path.parent().unwrap());
-    let mut lines: Vec<String> = dirs.iter().cloned().collect();
-    lines.sort();
-    let content = if lines.is_empty() { String::new() } else { lines.join("\n") + "\n" };
-    let _ = std::fs::write(&path, content);
-}
-
-
 fn collect_dir_paths(node: &TreeNode, out: &mut Vec<PathBuf>) {
     if !node.is_repo {
         out.push(node.path.clone());
@@ -1580,24 +2625,27 @@ fn collect_dir_paths(node: &TreeNode, out: &mut Vec<PathBuf>) {
     }
 }
 
-/// Spawn a background thread that runs `screen -ls`, `ps`, and workspace scan
-/// in parallel. Returns a receiver for the completed `RefreshData`.
-/// The UI can start immediately with stale data and apply the update on arrival.
-pub fn spawn_refresh(
-    workspace_dir: Option<PathBuf>,
-    dir_order: Vec<String>,
+/// Gather a refresh on a background thread. With `known` (the pid_names
+/// whose windows are already snapshotted) it's a timer tick: the tree isn't
+/// rescanned and only new sessions' windows are queried.
+fn spawn_refresh(
+    roots: Vec<WorkspaceRoot>,
+    scan_options: ScanOptions,
+    dir_order: HashMap<String, Vec<String>>,
+    known: Option<HashSet<String>>,
 ) -> std::sync::mpsc::Receiver<RefreshData> {
     let (tx, rx) = std::sync::mpsc::sync_channel(1);
     std::thread::spawn(move || {
+        let full = known.is_none();
         let (sessions, process_map, workspace_tree) = std::thread::scope(|s| {
             let sessions_h = s.spawn(|| screen::list_sessions().unwrap_or_default());
-            let ps_h = s.spawn(|| screen::build_process_map());
+            let ps_h = s.spawn(screen::build_process_map);
             let tree_h = s.spawn(move || {
-                workspace_dir.as_ref().map(|d| {
-                    let mut tree = workspace::scan_tree(d);
-                    if !dir_order.is_empty() {
-                        reorder_tree_children(&mut tree, &dir_order);
-                    }
+                if !full {
+                    return None;
+                }
+                workspace::scan_roots(&roots, &scan_options).map(|mut tree| {
+                    apply_dir_order(&mut tree, &roots, &dir_order);
                     tree
                 })
             });
@@ -1611,15 +2659,44 @@ pub fn spawn_refresh(
             .filter_map(|s| s.pid_name.split('.').next()?.parse().ok())
             .collect();
         let session_has_proc = screen::has_foreground_from_map(&process_map, &pids);
+        let session_windows = match &known {
+            None => screen::snapshot_sessions(&process_map, &sessions),
+            Some(known) => {
+                let new: Vec<Session> = sessions.iter().filter(|s| !known.contains(&s.pid_name)).cloned().collect();
+                screen::snapshot_sessions(&process_map, &new)
+            }
+        };
         let _ = tx.send(RefreshData {
             sessions,
             session_has_proc,
             workspace_tree,
+            session_windows,
+            full,
         });
     });
     rx
 }
 
+/// Identity of each listed session, in list order.
+fn session_keys(sessions: &[Session]) -> impl Iterator<Item = &str> {
+    sessions.iter().map(|s| s.pid_name.as_str())
+}
+
+/// Order each root's top-level directories by its saved order.
+fn apply_dir_order(tree: &mut TreeNode, roots: &[WorkspaceRoot], order: &HashMap<String, Vec<String>>) {
+    if let [root] = roots {
+        if let Some(o) = order.get(&root.name) {
+            reorder_tree_children(tree, o);
+        }
+        return;
+    }
+    for child in &mut tree.children {
+        if let Some(o) = order.get(&child.name) {
+            reorder_tree_children(child, o);
+        }
+    }
+}
+
 fn reorder_tree_children(tree: &mut TreeNode, order: &[String]) {
     let order_map: HashMap<&str, usize> = order.iter().enumerate()
         .map(|(i, name)| (name.as_str(), i))
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+use crate::screen;
+
+/// A scrollback dump taken just before a session was killed.
+#[derive(Clone, Debug)]
+pub struct Archive {
+    pub name: String,
+    /// unix timestamp of the kill
+    pub timestamp: u64,
+    pub dir: PathBuf,
+    /// directory the session lived in, used by undo
+    pub cwd: Option<PathBuf>,
+    pub windows: Vec<ArchivedWindow>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchivedWindow {
+    pub number: u32,
+    pub title: String,
+    pub cwd: Option<PathBuf>,
+    pub file: PathBuf,
+}
+
+fn archive_root() -> PathBuf {
+    paths::state_dir().join("archive")
+}
+
+/// Dump every window's scrollback into
+/// `<state dir>/archive/<name>/<timestamp>/` along with a `meta`
+/// file describing the windows, so the session can be brought back later.
+pub fn archive_session(
+    pid_name: &str,
+    name: &str,
+    cwd: Option<&Path>,
+    windows: &[screen::WindowInfo],
+) -> Result<PathBuf, String> {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir = archive_root().join(name).join(ts.to_string());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    // Fall back to a live query when the refresh snapshot has no windows
+    let numbers: Vec<u32> = if windows.is_empty() {
+        screen::list_windows(pid_name).into_iter().map(|(n, _)| n).collect()
+    } else {
+        windows.iter().map(|w| w.number).collect()
+    };
+
+    let mut meta = String::new();
+    if let Some(c) = cwd {
+        meta.push_str(&format!("cwd\t{}\n", c.display()));
+    }
+    let mut saved = 0;
+    for number in numbers {
+        let file = dir.join(format!("window-{number}.txt"));
+        if !screen::hardcopy(pid_name, number, &file, true) {
+            continue;
+        }
+        saved += 1;
+        let info = windows.iter().find(|w| w.number == number);
+        let title = info.map(|w| w.title.replace(['\t', '\n'], " ")).unwrap_or_default();
+        let win_cwd = info
+            .and_then(|w| w.cwd.as_ref())
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        meta.push_str(&format!("window\t{number}\t{title}\t{win_cwd}\n"));
+    }
+
+    if saved == 0 {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(format!("Nothing to archive for '{name}'"));
+    }
+    fs::write(dir.join("meta"), meta).map_err(|e| format!("Failed to write archive: {e}"))?;
+    Ok(dir)
+}
+
+/// All archives, newest first.
+pub fn list_archives() -> Vec<Archive> {
+    let mut archives = Vec::new();
+    let Ok(names) = fs::read_dir(archive_root()) else {
+        return archives;
+    };
+    for name_entry in names.flatten() {
+        let name = name_entry.file_name().to_string_lossy().into_owned();
+        let Ok(stamps) = fs::read_dir(name_entry.path()) else { continue };
+        for stamp in stamps.flatten() {
+            let Ok(timestamp) = stamp.file_name().to_string_lossy().parse::<u64>() else {
+                continue;
+            };
+            if let Some(a) = load_archive(&name, timestamp, &stamp.path()) {
+                archives.push(a);
+            }
+        }
+    }
+    archives.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(a.name.cmp(&b.name)));
+    archives
+}
+
+fn load_archive(name: &str, timestamp: u64, dir: &Path) -> Option<Archive> {
+    let meta = fs::read_to_string(dir.join("meta")).ok()?;
+    let mut cwd = None;
+    let mut windows = Vec::new();
+    for line in meta.lines() {
+        let mut fields = line.split('\t');
+        match fields.next() {
+            Some("cwd") => cwd = fields.next().map(PathBuf::from),
+            Some("window") => {
+                let Some(number) = fields.next().and_then(|n| n.parse().ok()) else { continue };
+                let title = fields.next().unwrap_or("").to_string();
+                let win_cwd = fields.next().filter(|c| !c.is_empty()).map(PathBuf::from);
+                windows.push(ArchivedWindow {
+                    number,
+                    title,
+                    cwd: win_cwd,
+                    file: dir.join(format!("window-{number}.txt")),
+                });
+            }
+            _ => {}
+        }
+    }
+    Some(Archive {
+        name: name.to_string(),
+        timestamp,
+        dir: dir.to_path_buf(),
+        cwd,
+        windows,
+    })
+}
+
+/// Archived output of every window, with a header line per window.
+pub fn read_archive(archive: &Archive) -> Vec<String> {
+    let mut lines = Vec::new();
+    for w in &archive.windows {
+        let header = if w.title.is_empty() {
+            format!("── window {} ──", w.number)
+        } else {
+            format!("── window {}: {} ──", w.number, w.title)
+        };
+        lines.push(header);
+        let text = fs::read(&w.file)
+            .map(|b| String::from_utf8_lossy(&b).into_owned())
+            .unwrap_or_default();
+        lines.extend(text.trim_end().lines().map(|l| l.to_string()));
+        lines.push(String::new());
+    }
+    lines
+}
+
+pub fn delete_archive(archive: &Archive) -> Result<(), String> {
+    fs::remove_dir_all(&archive.dir).map_err(|e| format!("Failed to delete archive: {e}"))?;
+    // Drop the per-name directory once its last archive is gone
+    if let Some(parent) = archive.dir.parent() {
+        let _ = fs::remove_dir(parent);
+    }
+    Ok(())
+}
+
+/// Bring a killed session back: one window per archived window, each in its
+/// old directory, printing the archived output before handing over to a shell.
+/// Built the same way as a restored session.
+pub fn undo_kill(archive: &Archive) -> Result<(), screen::Failure> {
+    if archive.windows.is_empty() {
+        return Err(screen::Failure::Other(format!("Archive for '{}' has no windows", archive.name)));
+    }
+    let windows: Vec<screen::WindowInfo> = archive
+        .windows
+        .iter()
+        .map(|w| screen::WindowInfo {
+            number: w.number,
+            title: w.title.clone(),
+            cwd: w.cwd.clone(),
+            command: None,
+        })
+        .collect();
+    let replay = |w: &screen::WindowInfo| -> Vec<String> {
+        let Some(archived) = archive.windows.iter().find(|a| a.number == w.number) else {
+            return Vec::new();
+        };
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            r#"cat -- "$0"; exec "${SHELL:-/bin/sh}""#.to_string(),
+            archived.file.to_string_lossy().into_owned(),
+        ]
+    };
+    let dir = archive.cwd.as_deref().filter(|d| d.is_dir());
+    screen::rebuild_session(&archive.name, dir, &windows, &replay, false)
+}
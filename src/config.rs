@@ -1,21 +1,71 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub struct Config {
-    pub workspace: Option<PathBuf>,
+use crate::hooks::HookSet;
+use crate::paths;
+use crate::statefile;
+use crate::workspace::ScanOptions;
+
+/// One workspace root: a directory of repos shown under its own name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkspaceRoot {
+    pub name: String,
+    pub path: PathBuf,
 }
 
-impl Config {
-    pub fn load(cli_workspace: Option<&str>) -> Self {
-        // CLI arg takes precedence for workspace
-        if let Some(ws) = cli_workspace {
-            return Self {
-                workspace: Some(expand_tilde(ws)),
-            };
+impl WorkspaceRoot {
+    /// `name=path`, or a bare path named after its last component.
+    pub fn parse(spec: &str) -> Self {
+        if let Some((name, path)) = spec.split_once('=') {
+            let name = name.trim();
+            if !name.is_empty() && !name.contains('/') {
+                return Self { name: name.to_string(), path: expand_tilde(path.trim()) };
+            }
         }
+        let path = expand_tilde(spec);
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("workspace")
+            .to_string();
+        Self { name, path }
+    }
+}
+
+pub struct Config {
+    /// Workspace roots in config order; `workspace` may be given repeatedly
+    pub workspaces: Vec<WorkspaceRoot>,
+    /// Re-run each window's foreground command when restoring sessions
+    pub restore_commands: bool,
+    /// Desktop notifications (notify-send / osascript) for watched sessions
+    pub notify: bool,
+    /// Shell command run on watch events, with SCRN_EVENT and SCRN_SESSION set
+    pub notify_hook: Option<String>,
+    /// Seconds without output before a watched session counts as quiet
+    pub silence_secs: u64,
+    /// `[hooks]` and `[hooks."<glob>"]` sections, in file order
+    pub hooks: Vec<HookSet>,
+    /// `[scan]` section: depth limit, ignore globs, symlink handling
+    pub scan: ScanOptions,
+    /// Seconds between background refreshes while the picker is open; 0 is off
+    pub refresh_interval: u64,
+    /// `log_level`: error, warn, info, debug or trace; `SCRN_LOG` overrides it
+    pub log_level: Option<String>,
+}
 
-        // Try config file
-        let mut workspace = None;
+impl Config {
+    pub fn load(cli_workspaces: &[String]) -> Self {
+        let mut workspaces = Vec::new();
+        let mut restore_commands = false;
+        let mut notify = false;
+        let mut notify_hook = None;
+        let mut silence_secs = 30;
+        let mut hooks: Vec<HookSet> = Vec::new();
+        let mut scan = ScanOptions::default();
+        let mut refresh_interval = 30;
+        let mut log_level = None;
+        // Set while inside a section; unknown sections are skipped
+        let mut section = String::new();
 
         if let Some(contents) = read_config_file() {
             for line in contents.lines() {
@@ -23,12 +73,60 @@ impl Config {
                 if line.is_empty() || line.starts_with('#') {
                     continue;
                 }
+                if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                    section = header.trim().to_string();
+                    if let Some(pattern) = hook_section_pattern(&section) {
+                        hooks.push(HookSet { pattern, ..HookSet::default() });
+                    }
+                    continue;
+                }
                 if let Some((key, value)) = line.split_once('=') {
                     let key = key.trim();
-                    let value = value.trim().trim_matches('"');
+                    if section == "scan" {
+                        match key {
+                            "max_depth" => scan.max_depth = value.trim().parse().ok().filter(|&d| d > 0),
+                            "ignore" => scan.ignore = parse_list(value),
+                            "follow_symlinks" => scan.follow_symlinks = value.trim() == "true",
+                            "markers" => scan.markers = parse_list(value),
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    let value = parse_value(value);
+                    let value = value.as_str();
+                    if !section.is_empty() {
+                        if hook_section_pattern(&section).is_some() {
+                            if let Some(set) = hooks.last_mut() {
+                                set.set(key, value);
+                            }
+                        }
+                        continue;
+                    }
                     match key {
                         "workspace" if !value.is_empty() => {
-                            workspace = Some(expand_tilde(value));
+                            workspaces.push(WorkspaceRoot::parse(value));
+                        }
+                        "restore_commands" => {
+                            restore_commands = value == "true";
+                        }
+                        "notify" => {
+                            notify = value == "true";
+                        }
+                        "notify_hook" if !value.is_empty() => {
+                            notify_hook = Some(value.to_string());
+                        }
+                        "log_level" if !value.is_empty() => {
+                            log_level = Some(value.to_string());
+                        }
+                        "refresh_interval" => {
+                            if let Ok(n) = value.parse::<u64>() {
+                                refresh_interval = n;
+                            }
+                        }
+                        "silence_secs" => {
+                            if let Ok(n) = value.parse::<u64>() {
+                                silence_secs = n.max(1);
+                            }
                         }
                         _ => {}
                     }
@@ -36,28 +134,220 @@ impl Config {
             }
         }
 
-        Self { workspace }
+        // CLI args replace the configured workspaces
+        if !cli_workspaces.is_empty() {
+            workspaces = cli_workspaces.iter().map(|w| WorkspaceRoot::parse(w)).collect();
+        }
+
+        Self {
+            workspaces,
+            restore_commands,
+            notify,
+            notify_hook,
+            silence_secs,
+            hooks,
+            scan,
+            refresh_interval,
+            log_level,
+        }
     }
 }
 
+/// Per-repo overrides from `.scrn.toml` in the repo root.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RepoConfig {
+    /// Run in the first window when the session is created
+    pub command: Option<String>,
+    /// Extra windows opened after the first, from `[[windows]]` tables
+    pub windows: Vec<RepoWindow>,
+    /// `[env]` table, set for the whole session
+    pub env: Vec<(String, String)>,
+    /// Shown in the tree instead of the directory name
+    pub label: Option<String>,
+    pub color: Option<(u8, u8, u8)>,
+    /// Leave the repo out of the workspace tree
+    pub hidden: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RepoWindow {
+    pub title: Option<String>,
+    pub command: Option<String>,
+}
+
+impl RepoConfig {
+    /// Whether opening the repo runs anything it supplies: a command, a
+    /// window command or environment.
+    pub fn runs_commands(&self) -> bool {
+        self.command.is_some() || !self.env.is_empty() || self.windows.iter().any(|w| w.command.is_some())
+    }
+}
+
+/// Read `<repo>/.scrn.toml`, if there is one.
+pub fn load_repo_config(repo: &Path) -> Option<RepoConfig> {
+    load_repo_config_hashed(repo).map(|(cfg, _)| cfg)
+}
+
+/// Like `load_repo_config`, with the hash of the file's contents that
+/// trusting it is keyed by.
+pub fn load_repo_config_hashed(repo: &Path) -> Option<(RepoConfig, u64)> {
+    let contents = fs::read_to_string(repo.join(".scrn.toml")).ok()?;
+    Some((parse_repo_config(&contents), statefile::fnv1a(contents.as_bytes())))
+}
+
+fn parse_repo_config(contents: &str) -> RepoConfig {
+    let mut cfg = RepoConfig::default();
+    let mut section = String::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line == "[[windows]]" {
+            cfg.windows.push(RepoWindow::default());
+            section = "windows".to_string();
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = header.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim().trim_matches('"');
+        let value = parse_value(value);
+        match section.as_str() {
+            "" => match key {
+                "command" if !value.is_empty() => cfg.command = Some(value),
+                "label" if !value.is_empty() => cfg.label = Some(value),
+                "color" => cfg.color = parse_color(&value),
+                "hidden" => cfg.hidden = value == "true",
+                _ => {}
+            },
+            "env" => cfg.env.push((key.to_string(), value)),
+            "windows" => {
+                let Some(w) = cfg.windows.last_mut() else { continue };
+                match key {
+                    "title" if !value.is_empty() => w.title = Some(value),
+                    "command" if !value.is_empty() => w.command = Some(value),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    cfg
+}
+
+/// `#rrggbb` or one of a few colour names.
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some((channel(0)?, channel(2)?, channel(4)?));
+    }
+    let rgb = match value.to_lowercase().as_str() {
+        "red" => (230, 110, 110),
+        "green" => (110, 210, 130),
+        "yellow" => (230, 200, 90),
+        "blue" => (110, 160, 240),
+        "magenta" => (210, 130, 220),
+        "cyan" => (100, 210, 220),
+        "orange" => (240, 160, 80),
+        "gray" | "grey" => (150, 150, 160),
+        "white" => (235, 235, 240),
+        _ => return None,
+    };
+    Some(rgb)
+}
+
+/// A TOML-ish string value: `"basic"` with `\"` `\\` `\n` `\t` escapes,
+/// `'literal'`, or a bare word.
+pub fn parse_value(raw: &str) -> String {
+    let raw = raw.trim();
+    if let Some(inner) = raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')) {
+        return inner.to_string();
+    }
+    let Some(inner) = raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) else {
+        return raw.trim_matches('"').to_string();
+    };
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A one-line array of strings, `["a", 'b']`. A single bare string is a
+/// one-element list.
+pub fn parse_list(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    let Some(inner) = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) else {
+        let value = parse_value(raw);
+        return if value.is_empty() { Vec::new() } else { vec![value] };
+    };
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in inner.chars() {
+        match quote {
+            Some(q) => {
+                item.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == ',' => items.push(std::mem::take(&mut item)),
+            None => {
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                item.push(c);
+            }
+        }
+    }
+    items.push(item);
+    items
+        .iter()
+        .map(|i| parse_value(i))
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// `hooks` -> global section, `hooks."work/*"` -> glob section.
+fn hook_section_pattern(section: &str) -> Option<Option<String>> {
+    if section == "hooks" {
+        return Some(None);
+    }
+    let glob = section.strip_prefix("hooks.")?.trim().trim_matches('"');
+    Some(Some(glob.to_string()))
+}
+
 fn read_config_file() -> Option<String> {
-    let home = std::env::var("HOME").ok()?;
-    let path = PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("config.toml");
-    fs::read_to_string(path).ok()
+    fs::read_to_string(paths::config_file()).ok()
 }
 
 fn expand_tilde(path: &str) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/") {
-        if let Ok(home) = std::env::var("HOME") {
-            return PathBuf::from(home).join(rest);
-        }
+        return paths::home_dir().join(rest);
     } else if path == "~" {
-        if let Ok(home) = std::env::var("HOME") {
-            return PathBuf::from(home);
-        }
+        return paths::home_dir();
     }
     PathBuf::from(path)
 }
@@ -0,0 +1,321 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::screen;
+use crate::shell;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        }
+    }
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    /// What to do about a warn or fail.
+    fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Check { name, status: Status::Pass, detail, fix: None }
+    }
+
+    fn warn(name: &'static str, detail: String, fix: String) -> Self {
+        Check { name, status: Status::Warn, detail, fix: Some(fix) }
+    }
+
+    fn fail(name: &'static str, detail: String, fix: String) -> Self {
+        Check { name, status: Status::Fail, detail, fix: Some(fix) }
+    }
+}
+
+/// `scrn doctor [--json]`: check the environment scrn depends on and say
+/// how to fix what's wrong. Returns false when any check failed.
+pub fn run(args: &[String]) -> Result<bool, String> {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+
+    let sockets = screen::socket_report().ok();
+    let checks = vec![
+        check_screen(),
+        check_screenrc(),
+        check_dead_sockets(sockets.as_ref()),
+        check_socket_dir(sockets.as_ref().and_then(|s| s.dir.clone())),
+        check_flow_control(),
+        check_shell_integration(),
+    ];
+    let ok = checks.iter().all(|c| c.status != Status::Fail);
+
+    if json {
+        println!("{}", to_json(&checks, ok));
+    } else {
+        for check in &checks {
+            println!("{:<4}  {:<18} {}", check.status.label(), check.name, check.detail);
+            if let Some(fix) = &check.fix {
+                println!("{:<24} fix: {fix}", "");
+            }
+        }
+    }
+    Ok(ok)
+}
+
+/// Every `screen` executable on `$PATH`, in lookup order.
+fn screens_on_path() -> Vec<PathBuf> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut found: Vec<PathBuf> = Vec::new();
+    for dir in std::env::split_paths(&path) {
+        let candidate = dir.join("screen");
+        let executable = fs::metadata(&candidate)
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false);
+        if executable && !found.contains(&candidate) {
+            found.push(candidate);
+        }
+    }
+    found
+}
+
+fn check_screen() -> Check {
+    const NAME: &str = "screen version";
+    let screens = screens_on_path();
+    let Some(first) = screens.first() else {
+        return Check::fail(
+            NAME,
+            "no `screen` on PATH".to_string(),
+            "install GNU Screen 5: `brew install screen` (macOS) or your distro's package".to_string(),
+        );
+    };
+    let version = match screen::version(first) {
+        Ok(v) => v,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("couldn't run {}: {e}", first.display()),
+                "reinstall GNU Screen".to_string(),
+            )
+        }
+    };
+    if screen::version_major(&version) >= screen::MIN_SCREEN_MAJOR {
+        return Check::pass(NAME, format!("{version} ({})", first.display()));
+    }
+
+    let detail = format!(
+        "{} is Screen {version}; scrn needs {}.0+ for truecolor",
+        first.display(),
+        screen::MIN_SCREEN_MAJOR
+    );
+    // A newer screen further down PATH only needs PATH reordered.
+    let newer = screens[1..].iter().find(|s| {
+        screen::version(s)
+            .map(|v| screen::version_major(&v) >= screen::MIN_SCREEN_MAJOR)
+            .unwrap_or(false)
+    });
+    let fix = match newer.and_then(|s| s.parent()) {
+        Some(dir) => format!("put {} before {} in PATH", dir.display(), first.parent().unwrap_or(first).display()),
+        None => "upgrade: `brew install screen` (macOS) or build 5.x from https://ftp.gnu.org/gnu/screen/".to_string(),
+    };
+    Check::fail(NAME, detail, fix)
+}
+
+/// Lines of `rc` that undo what scrn's generated screenrc sets up.
+fn screenrc_overrides(rc: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for (i, line) in rc.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some(&command) = words.first() else {
+            continue;
+        };
+        let arg = |n: usize| words.get(n).map(|w| w.trim_matches('"')).unwrap_or("");
+        let overrides = match command {
+            "truecolor" => arg(1) == "off",
+            "defflow" | "flow" => matches!(arg(1), "on" | "auto"),
+            "bindkey" => arg(1) == "^S" && arg(2) != "detach",
+            "escape" => arg(1).starts_with("^S"),
+            _ => false,
+        };
+        if overrides {
+            found.push(format!("line {}: `{}`", i + 1, line.trim()));
+        }
+    }
+    found
+}
+
+fn check_screenrc() -> Check {
+    const NAME: &str = "screenrc";
+    let rc = screen::ensure_screenrc();
+    if !fs::read_to_string(&rc).is_ok_and(|c| c.contains("truecolor on")) {
+        let dir = Path::new(&rc).parent().unwrap_or(Path::new("/"));
+        return Check::fail(
+            NAME,
+            format!("couldn't write {rc}"),
+            format!("make {} writable by you", dir.display()),
+        );
+    }
+
+    let user_rc = paths::home_dir().join(".screenrc");
+    let Ok(contents) = fs::read_to_string(&user_rc) else {
+        return Check::pass(NAME, format!("{rc} (no ~/.screenrc)"));
+    };
+    let overrides = screenrc_overrides(&contents);
+    if overrides.is_empty() {
+        return Check::pass(NAME, format!("{rc} (sources ~/.screenrc)"));
+    }
+    Check::warn(
+        NAME,
+        format!("~/.screenrc overrides truecolor, flow control or the Ctrl+S detach: {}", overrides.join(", ")),
+        "remove those lines; scrn re-applies its settings for sessions it creates, but sessions started with plain `screen` keep them".to_string(),
+    )
+}
+
+fn check_dead_sockets(report: Option<&screen::SocketReport>) -> Check {
+    const NAME: &str = "dead sockets";
+    let Some(report) = report else {
+        return Check::warn(NAME, "couldn't run `screen -ls`".to_string(), "see the screen version check".to_string());
+    };
+    if report.dead.is_empty() {
+        return Check::pass(NAME, "none".to_string());
+    }
+    Check::warn(
+        NAME,
+        format!("{} socket(s) left by sessions that died: {}", report.dead.len(), report.dead.join(", ")),
+        "run `scrn wipe` (`--dry-run` to list them first)".to_string(),
+    )
+}
+
+fn check_socket_dir(reported: Option<PathBuf>) -> Check {
+    const NAME: &str = "socket dir";
+    let ours = paths::socket_dir();
+    let dir = reported.clone().unwrap_or_else(|| ours.clone());
+    let Ok(meta) = fs::metadata(&dir) else {
+        return Check::pass(NAME, format!("{} (not created yet)", dir.display()));
+    };
+
+    // SAFETY: getuid has no failure mode.
+    let uid = unsafe { libc::getuid() };
+    if meta.uid() != uid {
+        return Check::fail(
+            NAME,
+            format!("{} is owned by uid {}, not you (uid {uid}); screen refuses to use it", dir.display(), meta.uid()),
+            format!("sudo chown \"$USER\" {}", dir.display()),
+        );
+    }
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Check::fail(
+            NAME,
+            format!("{} has mode {mode:o}; screen requires 700", dir.display()),
+            format!("chmod 700 {}", dir.display()),
+        );
+    }
+    if reported.is_some_and(|r| r != ours) {
+        return Check::warn(
+            NAME,
+            format!(
+                "screen keeps sockets in {} but scrn looks in {}, so idle times and watching don't work",
+                dir.display(),
+                ours.display()
+            ),
+            format!("export SCREENDIR={}", dir.display()),
+        );
+    }
+    Check::pass(NAME, format!("{} (700)", dir.display()))
+}
+
+fn check_flow_control() -> Check {
+    const NAME: &str = "flow control";
+    match shell::flow_control_on() {
+        None => Check::pass(NAME, "stdin is not a terminal, skipped".to_string()),
+        Some(false) => Check::pass(NAME, "off".to_string()),
+        Some(true) => Check::warn(
+            NAME,
+            "on: Ctrl+S freezes the terminal outside scrn instead of reaching screen".to_string(),
+            "add `stty -ixon` to your shell rc, or install the shell integration".to_string(),
+        ),
+    }
+}
+
+fn check_shell_integration() -> Check {
+    const NAME: &str = "shell integration";
+    let shell_path = std::env::var("SHELL").unwrap_or_default();
+    let shell = shell_path.rsplit('/').next().unwrap_or("");
+    let rc_files = shell::rc_files(shell);
+    let Some(first) = rc_files.first() else {
+        return Check::warn(
+            NAME,
+            format!("none for {}", if shell.is_empty() { "an unknown shell" } else { shell }),
+            "run `stty -ixon` before scrn; integration exists for zsh and bash".to_string(),
+        );
+    };
+
+    let installed = rc_files.iter().find(|rc| {
+        fs::read_to_string(rc).is_ok_and(|contents| {
+            contents
+                .lines()
+                .any(|l| !l.trim_start().starts_with('#') && l.contains("scrn init"))
+        })
+    });
+    match installed {
+        Some(rc) => Check::pass(NAME, format!("{}", rc.display())),
+        None => Check::warn(
+            NAME,
+            format!("`scrn init {shell}` isn't in {}", first.display()),
+            format!("echo 'eval \"$(scrn init {shell})\"' >> {}", first.display()),
+        ),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn to_json(checks: &[Check], ok: bool) -> String {
+    let items: Vec<String> = checks
+        .iter()
+        .map(|c| {
+            format!(
+                "{{\"name\":{},\"status\":\"{}\",\"detail\":{},\"fix\":{}}}",
+                json_string(c.name),
+                c.status.label(),
+                json_string(&c.detail),
+                c.fix.as_deref().map(json_string).unwrap_or_else(|| "null".to_string())
+            )
+        })
+        .collect();
+    format!("{{\"ok\":{ok},\"checks\":[{}]}}", items.join(","))
+}
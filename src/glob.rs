@@ -0,0 +1,107 @@
+/// Shell-style glob match: `*` matches within one path segment, `**` across
+/// segments, `?` a single character. Case-sensitive, like paths.
+///
+/// Iterative, remembering only the last `*` and the last `**` to fall back
+/// to: a later star can absorb anything an earlier one could, so neither
+/// needs more than one resume point and the match stays polynomial.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // (pattern index after the star, text index it has absorbed up to)
+    let mut star: Option<(usize, usize)> = None;
+    // the same for `**`, and whether it was `**/`
+    let mut globstar: Option<(usize, usize, bool)> = None;
+
+    while ti < t.len() {
+        if p.get(pi) == Some(&'*') && p.get(pi + 1) == Some(&'*') {
+            let dir = p.get(pi + 2) == Some(&'/');
+            pi += if dir { 3 } else { 2 };
+            globstar = Some((pi, ti, dir));
+            star = None;
+        } else if p.get(pi) == Some(&'*') {
+            pi += 1;
+            star = Some((pi, ti));
+        } else if p.get(pi).is_some_and(|&c| c == t[ti] || (c == '?' && t[ti] != '/')) {
+            pi += 1;
+            ti += 1;
+        } else if let Some((resume, absorbed)) = star.filter(|&(_, absorbed)| t[absorbed] != '/') {
+            // `*` takes one more char, but never a `/`
+            star = Some((resume, absorbed + 1));
+            pi = resume;
+            ti = absorbed + 1;
+        } else if let Some((resume, absorbed, dir)) = globstar {
+            // `**` takes one more char, `**/` one more whole directory
+            let next = if dir {
+                match t[absorbed..].iter().position(|&c| c == '/') {
+                    Some(i) => absorbed + i + 1,
+                    None => return false,
+                }
+            } else {
+                absorbed + 1
+            };
+            globstar = Some((resume, next, dir));
+            star = None;
+            pi = resume;
+            ti = next;
+        } else {
+            return false;
+        }
+    }
+
+    // Trailing stars match the empty rest, and `**/` zero directories
+    loop {
+        if p[pi..].starts_with(&['*', '*', '/']) {
+            pi += 3;
+        } else if p.get(pi) == Some(&'*') {
+            pi += 1;
+        } else {
+            break;
+        }
+    }
+    pi == p.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_within_a_segment() {
+        assert!(glob_match("work/*", "work/api"));
+        assert!(!glob_match("work/*", "work/api/src"));
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("a*b*c", "aXbYc"));
+        assert!(!glob_match("a*c", "a/c"));
+    }
+
+    #[test]
+    fn globstar_crosses_segments() {
+        assert!(glob_match("work/**", "work/api/src"));
+        assert!(glob_match("**/target", "target"));
+        assert!(glob_match("**/target", "a/b/target"));
+        assert!(!glob_match("**/target", "a/btarget"));
+        assert!(glob_match("a/**/b", "a/b"));
+        assert!(glob_match("a/**/b", "a/x/y/b"));
+        assert!(glob_match("**.rs", "src/main.rs"));
+        assert!(glob_match("**/*.rs", "src/bin/main.rs"));
+        assert!(!glob_match("**/*.rs", "src/main.txt"));
+    }
+
+    #[test]
+    fn question_mark_is_one_non_slash_char() {
+        assert!(glob_match("ap?", "api"));
+        assert!(!glob_match("ap?", "ap"));
+        assert!(!glob_match("a?b", "a/b"));
+    }
+
+    #[test]
+    fn many_stars_stay_fast() {
+        let text = format!("{}/b", "a".repeat(200));
+        let started = std::time::Instant::now();
+        assert!(!glob_match("*a*a*a*a*a*a*a*a*a*a*c", &text));
+        assert!(!glob_match("**a**a**a**a**a**a**a**c", &text));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+}
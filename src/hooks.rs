@@ -0,0 +1,167 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+use crate::glob::glob_match;
+use crate::logging::{self, Logged};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookEvent {
+    Create,
+    Attach,
+    Detach,
+    Kill,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::Create => "create",
+            HookEvent::Attach => "attach",
+            HookEvent::Detach => "detach",
+            HookEvent::Kill => "kill",
+        }
+    }
+
+    /// Attach and detach hooks run while scrn has handed the terminal over,
+    /// so they are waited for and may write to it (a window title, say).
+    fn foreground(self) -> bool {
+        matches!(self, HookEvent::Attach | HookEvent::Detach)
+    }
+}
+
+/// Hook commands from a `[hooks]` or `[hooks."<glob>"]` config section.
+#[derive(Clone, Debug, Default)]
+pub struct HookSet {
+    /// `None` for the global `[hooks]` section
+    pub pattern: Option<String>,
+    pub on_create: Option<String>,
+    pub on_attach: Option<String>,
+    pub on_detach: Option<String>,
+    pub on_kill: Option<String>,
+}
+
+impl HookSet {
+    pub fn set(&mut self, key: &str, command: &str) -> bool {
+        let slot = match key {
+            "on_create" => &mut self.on_create,
+            "on_attach" => &mut self.on_attach,
+            "on_detach" => &mut self.on_detach,
+            "on_kill" => &mut self.on_kill,
+            _ => return false,
+        };
+        *slot = Some(command.to_string()).filter(|c| !c.is_empty());
+        true
+    }
+
+    fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::Create => self.on_create.as_deref(),
+            HookEvent::Attach => self.on_attach.as_deref(),
+            HookEvent::Detach => self.on_detach.as_deref(),
+            HookEvent::Kill => self.on_kill.as_deref(),
+        }
+    }
+}
+
+/// The session a hook fires for.
+pub struct HookTarget<'a> {
+    pub session: &'a str,
+    pub pid: Option<u32>,
+    /// repo (or working) directory of the session
+    pub repo: Option<&'a Path>,
+    /// repo path relative to the workspace, matched by section globs
+    pub rel_path: Option<&'a str>,
+}
+
+/// Commands for `event` whose section applies to the target: the global
+/// section first, then every glob section matching the workspace-relative
+/// repo path or the session name.
+pub fn commands_for<'a>(hooks: &'a [HookSet], event: HookEvent, target: &HookTarget) -> Vec<&'a str> {
+    hooks
+        .iter()
+        .filter(|h| match &h.pattern {
+            None => true,
+            Some(p) => {
+                target.rel_path.is_some_and(|rel| glob_match(p, rel)) || glob_match(p, target.session)
+            }
+        })
+        .filter_map(|h| h.command(event))
+        .collect()
+}
+
+/// Run hook commands through `sh -c` in the repo directory with
+/// SCRN_EVENT, SCRN_SESSION, SCRN_PID and SCRN_REPO set. Attach and detach
+/// hooks run in the foreground with the terminal as stdout; the others run
+/// detached with their output discarded. A failed start or non-zero exit is
+/// logged.
+pub fn run(commands: &[&str], event: HookEvent, target: &HookTarget) {
+    for command in commands {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command])
+            .env("SCRN_EVENT", event.as_str())
+            .env("SCRN_SESSION", target.session)
+            .env("SCRN_PID", target.pid.map(|p| p.to_string()).unwrap_or_default())
+            .env(
+                "SCRN_REPO",
+                target.repo.map(|r| r.to_string_lossy().into_owned()).unwrap_or_default(),
+            );
+        if let Some(dir) = target.repo.filter(|r| r.is_dir()) {
+            cmd.current_dir(dir);
+        }
+        let what = format!("{} hook '{command}'", event.as_str());
+        if event.foreground() {
+            run_foreground(cmd, &what);
+        } else {
+            spawn_detached(cmd, &what);
+        }
+    }
+}
+
+/// Run `cmd` to completion with stdout on the terminal, logging a failed
+/// start or a non-zero exit.
+fn run_foreground(mut cmd: Command, what: &str) {
+    let output = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .logged_output();
+    match output {
+        Ok(out) if !out.status.success() => {
+            logging::warn(&format!("{what} exited with {}", out.status));
+        }
+        Ok(_) => {}
+        Err(e) => logging::error(&format!("Failed to run {what}: {e}")),
+    }
+}
+
+/// Spawn `cmd` and reap it on a throwaway thread, logging a failed start or
+/// a non-zero exit. The command itself is traced at debug level.
+pub fn spawn_detached(mut cmd: Command, what: &str) {
+    let started = Instant::now();
+    let child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let what = what.to_string();
+    let line = logging::describe(&cmd);
+    match child {
+        Ok(child) => {
+            std::thread::spawn(move || {
+                let (status, stderr) = match child.wait_with_output() {
+                    Ok(out) => (Ok(out.status), out.stderr),
+                    Err(e) => (Err(e), Vec::new()),
+                };
+                logging::log_command(&line, started, &status, &[], &stderr);
+                match status {
+                    Ok(status) if !status.success() => {
+                        logging::warn(&format!("{what} exited with {status}"));
+                    }
+                    _ => {}
+                }
+            });
+        }
+        Err(e) => logging::error(&format!("Failed to run {what}: {e}")),
+    }
+}
@@ -1,22 +1,23 @@
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-fn log_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("scrn")
-        .join("scrn.log")
-}
+use crate::paths;
+use crate::statefile;
 
 fn utc_timestamp() -> String {
     let secs = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
+    format_utc(secs)
+}
 
+/// Format unix seconds as `YYYY-MM-DD HH:MM:SS` (UTC).
+pub fn format_utc(secs: u64) -> String {
     let s = secs % 60;
     let m = (secs / 60) % 60;
     let h = (secs / 3600) % 24;
@@ -50,11 +51,263 @@ fn utc_timestamp() -> String {
     )
 }
 
-pub fn log_error(msg: &str) {
-    let path = log_path();
+/// Log verbosity, most severe first. Each level includes the ones above it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    /// Every spawned command with its exit status, stderr and duration
+    Debug,
+    /// Also the stdout of every spawned command
+    Trace,
+}
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "none" => Some(Self::Off),
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+/// Rotate `scrn.log` to `scrn.log.1` once it grows past this.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Rotated logs kept: `scrn.log.1` (newest) to `scrn.log.<KEEP>`.
+const KEEP_LOGS: u32 = 3;
+/// Captured output longer than this is cut in the log.
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+static LEVEL: OnceLock<Level> = OnceLock::new();
+
+/// Set the level from `SCRN_LOG`, or else `configured` (the `log_level`
+/// config key). Until this runs only `SCRN_LOG` is consulted.
+pub fn init(configured: Option<&str>) {
+    let _ = LEVEL.set(env_level().or(configured.and_then(Level::parse)).unwrap_or(Level::Info));
+}
+
+fn env_level() -> Option<Level> {
+    std::env::var("SCRN_LOG").ok().as_deref().and_then(Level::parse)
+}
+
+pub fn level() -> Level {
+    LEVEL.get().copied().or_else(env_level).unwrap_or(Level::Info)
+}
+
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level <= self::level()
+}
+
+pub fn log(level: Level, msg: &str) {
+    if !enabled(level) {
+        return;
+    }
+    let path = paths::log_file();
     let _ = std::fs::create_dir_all(path.parent().unwrap());
+    rotate(&path);
     if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(&path) {
-        let _ = writeln!(f, "[{}] {}", utc_timestamp(), msg);
+        let _ = writeln!(f, "[{}] {:<5} {}", utc_timestamp(), level.as_str(), msg);
+    }
+}
+
+pub fn error(msg: &str) {
+    log(Level::Error, msg);
+}
+
+pub fn warn(msg: &str) {
+    log(Level::Warn, msg);
+}
+
+pub fn info(msg: &str) {
+    log(Level::Info, msg);
+}
+
+pub fn debug(msg: &str) {
+    log(Level::Debug, msg);
+}
+
+/// Shift `scrn.log` -> `.1` -> `.2` ... once it is too big. The lock keeps
+/// two instances from rotating the same file twice.
+fn rotate(path: &Path) {
+    let too_big = |p: &Path| fs::metadata(p).is_ok_and(|m| m.len() > MAX_LOG_BYTES);
+    if !too_big(path) {
+        return;
+    }
+    let Ok(_lock) = statefile::lock(path) else { return };
+    if !too_big(path) {
+        return;
+    }
+    let numbered = |n: u32| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{n}"));
+        path.with_file_name(name)
+    };
+    let _ = fs::remove_file(numbered(KEEP_LOGS));
+    for n in (1..KEEP_LOGS).rev() {
+        let _ = fs::rename(numbered(n), numbered(n + 1));
+    }
+    let _ = fs::rename(path, numbered(1));
+}
+
+/// The command line, with arguments quoted where the shell would need it.
+pub fn describe(cmd: &Command) -> String {
+    let plain = |s: &str| {
+        !s.is_empty()
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,^".contains(c))
+    };
+    let mut out = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        let arg = arg.to_string_lossy();
+        out.push(' ');
+        if plain(&arg) {
+            out.push_str(&arg);
+        } else {
+            out.push_str(&format!("{arg:?}"));
+        }
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        out.push_str(&format!(" (in {})", dir.display()));
+    }
+    out
+}
+
+fn clip(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end();
+    let mut out: String = text.chars().take(MAX_OUTPUT_CHARS).collect();
+    if out.len() < text.len() {
+        out.push_str(" [...]");
+    }
+    out.replace('\n', "\n    ")
+}
+
+/// Log a finished command: what ran, how it ended and how long it took at
+/// debug level, plus its stderr (and at trace level its stdout) when they
+/// were captured. A command that couldn't be started is a warning.
+pub fn log_command(
+    what: &str,
+    started: Instant,
+    result: &io::Result<ExitStatus>,
+    stdout: &[u8],
+    stderr: &[u8],
+) {
+    let ms = started.elapsed().as_millis();
+    let status = match result {
+        Ok(status) => status.to_string(),
+        Err(e) => {
+            warn(&format!("$ {what}: failed to start: {e} ({ms} ms)"));
+            return;
+        }
+    };
+    if !enabled(Level::Debug) {
+        return;
+    }
+    let mut msg = format!("$ {what}: {status} ({ms} ms)");
+    if !stderr.trim_ascii().is_empty() {
+        msg.push_str(&format!("\n    stderr: {}", clip(stderr)));
+    }
+    if enabled(Level::Trace) && !stdout.trim_ascii().is_empty() {
+        msg.push_str(&format!("\n    stdout: {}", clip(stdout)));
+    }
+    debug(&msg);
+}
+
+/// `Command::output` and `Command::status`, logged with `log_command`.
+pub trait Logged {
+    fn logged_output(&mut self) -> io::Result<Output>;
+    fn logged_status(&mut self) -> io::Result<ExitStatus>;
+}
+
+impl Logged for Command {
+    fn logged_output(&mut self) -> io::Result<Output> {
+        let started = Instant::now();
+        let result = self.output();
+        let (status, stdout, stderr) = match &result {
+            Ok(o) => (Ok(o.status), o.stdout.as_slice(), o.stderr.as_slice()),
+            Err(e) => (Err(io::Error::new(e.kind(), e.to_string())), &[][..], &[][..]),
+        };
+        log_command(&describe(self), started, &status, stdout, stderr);
+        result
+    }
+
+    fn logged_status(&mut self) -> io::Result<ExitStatus> {
+        let started = Instant::now();
+        let result = self.status();
+        log_command(&describe(self), started, &result, &[], &[]);
+        result
+    }
+}
+
+/// `scrn log [-n <lines>] [-f]`: print the end of the log, and with `-f`
+/// keep printing what is appended, across rotations.
+pub fn tail(args: &[String]) -> Result<(), String> {
+    let mut lines = 50;
+    let mut follow = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-f" | "--follow" => follow = true,
+            "-n" | "--lines" => {
+                let v = args.get(i + 1).ok_or("-n needs a number of lines")?;
+                lines = v.parse().map_err(|_| format!("bad line count '{v}'"))?;
+                i += 1;
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+        i += 1;
+    }
+
+    let path = paths::log_file();
+    let contents = fs::read(&path).unwrap_or_default();
+    let text = String::from_utf8_lossy(&contents);
+    let all: Vec<&str> = text.lines().collect();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("{line}");
+    }
+    if !follow {
+        if contents.is_empty() {
+            eprintln!("scrn log: {} is empty", path.display());
+        }
+        return Ok(());
+    }
+
+    let mut offset = contents.len() as u64;
+    let mut stdout = io::stdout();
+    loop {
+        std::thread::sleep(Duration::from_millis(250));
+        let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if len < offset {
+            // Rotated: the new file starts from scratch
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+        let Ok(mut f) = File::open(&path) else { continue };
+        let mut new = Vec::new();
+        if f.seek(SeekFrom::Start(offset)).is_ok() && f.read_to_end(&mut new).is_ok() {
+            offset += new.len() as u64;
+            let _ = stdout.write_all(&new);
+            let _ = stdout.flush();
+        }
     }
 }
 
@@ -72,7 +325,7 @@ pub fn setup_panic_hook() {
             .location()
             .map(|l| format!(" at {}:{}:{}", l.file(), l.line(), l.column()))
             .unwrap_or_default();
-        log_error(&format!("PANIC: {msg}{location}"));
+        error(&format!("PANIC: {msg}{location}"));
         default(info);
     }));
 }
@@ -1,14 +1,27 @@
 mod app;
+mod archive;
 mod config;
+mod doctor;
+mod glob;
+mod hooks;
 mod logging;
+mod monitor;
+mod paths;
+mod pattern;
+mod query;
+mod report;
 mod screen;
+mod scrollback;
 mod shell;
+mod state;
+mod statefile;
+mod transfer;
 mod ui;
+mod wipe;
 mod workspace;
 
 use std::io;
 use std::process::Command;
-use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
 
 use crossterm::event::{
@@ -24,6 +37,7 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 
 use app::{Action, App, Mode};
+use hooks::HookEvent;
 
 fn input_insert(s: &mut String, cursor: &mut usize, c: char) {
     let bp = s
@@ -71,6 +85,65 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Some("log") => {
+            if let Err(e) = logging::tail(&args[2..]) {
+                eprintln!("scrn log: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some("paths") => {
+            paths::print();
+            return Ok(());
+        }
+        Some("doctor") => {
+            let cfg = config::Config::load(&[]);
+            logging::init(cfg.log_level.as_deref());
+            match doctor::run(&args[2..]) {
+                Ok(true) => return Ok(()),
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("scrn doctor: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some("wipe") => {
+            let cfg = config::Config::load(&[]);
+            logging::init(cfg.log_level.as_deref());
+            if let Err(e) = wipe::run(&args[2..]) {
+                eprintln!("scrn wipe: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some("export") => {
+            let cfg = config::Config::load(&[]);
+            logging::init(cfg.log_level.as_deref());
+            state::migrate(&cfg.workspaces);
+            print!("{}", transfer::export(&cfg.workspaces));
+            return Ok(());
+        }
+        Some("import") => {
+            let cfg = config::Config::load(&[]);
+            logging::init(cfg.log_level.as_deref());
+            state::migrate(&cfg.workspaces);
+            if let Err(e) = transfer::import(&args[2..], &cfg.workspaces) {
+                eprintln!("scrn import: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some("report") => {
+            let cfg = config::Config::load(&[]);
+            logging::init(cfg.log_level.as_deref());
+            state::migrate(&cfg.workspaces);
+            if let Err(e) = report::run(&args[2..], &state::load().tags) {
+                eprintln!("scrn report: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
         _ => {}
     }
 
@@ -81,13 +154,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Parse flags
-    let mut cli_workspace = None;
+    let mut cli_workspaces = Vec::new();
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "--workspace" | "-w" => {
                 if let Some(path) = args.get(i + 1) {
-                    cli_workspace = Some(path.clone());
+                    cli_workspaces.push(path.clone());
                     i += 2;
                     continue;
                 }
@@ -97,14 +170,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         i += 1;
     }
 
-    let cfg = config::Config::load(cli_workspace.as_deref());
+    let cfg = config::Config::load(&cli_workspaces);
+    logging::init(cfg.log_level.as_deref());
 
     // Disable flow control so Ctrl+S reaches screen as the detach key
-    disable_flow_control();
+    shell::disable_flow_control();
 
-    let mut app = App::new(cfg.workspace);
-    app.refresh_sessions();
-    app.restore_sessions();
+    let mut app = App::new(&cfg);
+    app.start_loading();
 
     // Set up terminal once for the whole session lifetime — no flash between cycles.
     let mut stdout = io::stdout();
@@ -120,85 +193,117 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     terminal.clear()?;
 
     // Main loop: show picker → spawn screen → wait for detach → repeat
-    let mut pending_refresh: Option<Receiver<app::RefreshData>> = None;
     loop {
-        let action = run_picker(&mut app, &mut terminal, pending_refresh.take())?;
+        let action = run_picker(&mut app, &mut terminal)?;
 
         match action {
             Action::Attach(ref pid_name) => {
-                yield_terminal(&mut terminal)?;
+                let session_name = pid_name.split('.').nth(1).unwrap_or(pid_name).to_string();
 
-                let pn1 = pid_name.clone();
-                let pn2 = pid_name.clone();
-                let t1 = std::thread::spawn(move || {
-                    Command::new("screen")
-                        .args(["-S", &pn1, "-X", "bindkey", "^S", "detach"])
-                        .status()
-                });
-                let t2 = std::thread::spawn(move || {
-                    Command::new("screen")
-                        .args(["-S", &pn2, "-X", "defflow", "off"])
-                        .status()
-                });
-                t1.join().ok();
-                t2.join().ok();
+                // These double as a check that the session is still there
+                let pn = pid_name.clone();
+                let flow = std::thread::spawn(move || screen::send(&pn, &["defflow", "off"]));
+                let bound = screen::send(pid_name, &["bindkey", "^S", "detach"]);
+                let ready = bound.and(flow.join().unwrap_or(Ok(())));
+                if let Err(failure) = ready {
+                    app.attach_failed(&session_name, failure);
+                    app.action = Action::None;
+                    app.start_refresh();
+                    continue;
+                }
 
-                let session_name = pid_name.split('.').nth(1).unwrap_or(pid_name).to_string();
+                let mut warning = None;
                 if let Some(cmd) = app.constant_command(&session_name) {
                     let stuff = format!("{}\n", cmd);
-                    let _ = Command::new("screen")
-                        .args(["-S", pid_name, "-X", "stuff", &stuff])
-                        .status();
+                    if let Err(e) = screen::send(pid_name, &["stuff", &stuff]) {
+                        warning = Some(format!("Error: couldn't start '{session_name}' command: {e}"));
+                    }
                 }
 
+                yield_terminal(&mut terminal)?;
                 app.mark_attached(&session_name);
+                app.run_hooks(HookEvent::Attach, &session_name, pid_name);
 
                 let rc = screen::ensure_screenrc();
-                let _ = Command::new("screen")
-                    .args(["-c", &rc, "-d", "-r", pid_name])
-                    .status();
+                let attached = screen::attach(&rc, pid_name, true);
 
+                app.run_hooks(HookEvent::Detach, &session_name, pid_name);
                 reclaim_terminal(&mut terminal)?;
+                app.mark_detached(&session_name);
+                app.monitor.clear(pid_name);
+                match attached {
+                    Err(failure) => app.attach_failed(&session_name, failure),
+                    Ok(()) => {
+                        if let Some(w) = warning {
+                            app.set_status(w);
+                        }
+                    }
+                }
                 app.action = Action::None;
-                pending_refresh = Some(app::spawn_refresh(
-                    app.workspace_dir.clone(),
-                    app.dir_order.clone(),
-                ));
+                app.start_refresh();
             }
             Action::Create(ref name, ref maybe_dir) => {
-                yield_terminal(&mut terminal)?;
                 let rc = screen::ensure_screenrc();
 
-                let has_command = app.constant_command(name).is_some();
+                let repo_cfg = app.repo_config(name, maybe_dir.as_deref()).unwrap_or_default();
+                // A constant's own command wins over the repo's `.scrn.toml`
+                let startup = app
+                    .constant_command(name)
+                    .map(|c| c.to_string())
+                    .or(repo_cfg.command.clone());
 
                 let mut cmd = Command::new("screen");
                 cmd.args(["-c", &rc, "-dmS", name]);
+                cmd.envs(repo_cfg.env.iter().map(|(k, v)| (k, v)));
                 if let Some(ref dir) = maybe_dir {
                     cmd.current_dir(dir);
                 }
-                let _ = cmd.status();
-
-                if has_command {
-                    if let Some(c) = app.constant_command(name) {
-                        let stuff = format!("{}\n", c);
-                        let _ = Command::new("screen")
-                            .args(["-S", name, "-X", "stuff", &stuff])
-                            .status();
+                if let Err(e) = screen::run(&mut cmd) {
+                    app.set_status(format!("Error: couldn't create '{name}': {e}"));
+                    app.action = Action::None;
+                    app.start_refresh();
+                    continue;
+                }
+
+                let mut warning = None;
+                if let Some(c) = startup {
+                    let stuff = format!("{}\n", c);
+                    if let Err(e) = screen::send(name, &["stuff", &stuff]) {
+                        warning = Some(format!("Error: couldn't start '{name}' command: {e}"));
+                    }
+                }
+                if !repo_cfg.windows.is_empty() {
+                    for (i, w) in repo_cfg.windows.iter().enumerate() {
+                        let number = i as u32 + 1;
+                        if let Err(e) = screen::add_window(name, number, w.title.as_deref(), w.command.as_deref()) {
+                            warning.get_or_insert(format!("Error: couldn't open window {number} in '{name}': {e}"));
+                        }
+                    }
+                    if let Err(e) = screen::send(name, &["select", "0"]) {
+                        warning.get_or_insert(format!("Error: couldn't select window 0 in '{name}': {e}"));
                     }
                 }
 
+                yield_terminal(&mut terminal)?;
+                app.run_hooks(HookEvent::Create, name, "");
                 app.mark_attached(name);
+                app.run_hooks(HookEvent::Attach, name, "");
 
-                let _ = Command::new("screen")
-                    .args(["-c", &rc, "-r", name])
-                    .status();
+                let attached = screen::attach(&rc, name, false);
 
+                app.run_hooks(HookEvent::Detach, name, "");
                 reclaim_terminal(&mut terminal)?;
+                app.mark_detached(name);
+                match attached {
+                    Err(failure) => app.attach_failed(name, failure),
+                    Ok(()) => {
+                        if let Some(w) = warning {
+                            app.set_status(w);
+                        }
+                    }
+                }
                 app.action = Action::None;
-                pending_refresh = Some(app::spawn_refresh(
-                    app.workspace_dir.clone(),
-                    app.dir_order.clone(),
-                ));
+                app.start_refresh();
             }
             Action::Quit | Action::None => break,
         }
@@ -265,20 +370,18 @@ fn hit_test_row(app: &App, row: u16) -> Option<usize> {
 fn run_picker(
     app: &mut App,
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    refresh_rx: Option<Receiver<app::RefreshData>>,
 ) -> Result<Action, Box<dyn std::error::Error>> {
-    let mut refresh_rx = refresh_rx;
-
     loop {
         terminal.draw(|f| ui::draw(f, app))?;
 
-        // Apply background refresh data as soon as it arrives
-        if let Some(ref rx) = refresh_rx {
-            if let Ok(data) = rx.try_recv() {
-                app.apply_refresh_data(data);
-                refresh_rx = None;
-            }
-        }
+        // Apply background refresh and restore results as soon as they arrive
+        app.poll_refresh();
+        app.poll_restore();
+        app.poll_kills();
+        app.poll_content_search();
+        app.tick_auto_refresh();
+
+        app.poll_monitor();
 
         // Auto-clear stale status messages
         if !app.status_msg.is_empty()
@@ -325,16 +428,21 @@ fn run_picker(
                         KeyCode::Char('`') => app.jump_to_last(),
                         KeyCode::Char('p') => app.start_pin_confirm(),
                         KeyCode::Char('C') => app.start_constant_confirm(),
-                        KeyCode::Tab => {
-                            if !app.search_input.is_empty() {
-                                app.toggle_search_filter();
-                            }
+                        KeyCode::Tab if !app.search_input.is_empty() => {
+                            app.toggle_search_filter();
                         }
                         KeyCode::Char('r') => app.refresh_sessions(),
                         KeyCode::Char('t') => app.create_throwaway(),
                         KeyCode::Char('e') => app.start_command_edit(),
+                        KeyCode::Char('T') => app.start_tag_edit(),
+                        KeyCode::Char('W') => app.start_worktree_create(),
+                        KeyCode::Char('D') => app.start_worktree_remove(),
+                        KeyCode::Char('w') => app.toggle_watch(),
+                        KeyCode::Char('n') => app.toggle_finish_watch(),
                         KeyCode::Char('O') => app.start_ordering(),
                         KeyCode::Char('R') => app.start_constant_ordering(),
+                        KeyCode::Char('a') => app.open_archives(),
+                        KeyCode::Char('F') => app.start_content_search(),
                         _ => {}
                     },
                     Mode::Searching => match key.code {
@@ -358,15 +466,11 @@ fn run_picker(
                     Mode::Creating => match key.code {
                         KeyCode::Enter => app.confirm_create(),
                         KeyCode::Esc => app.cancel_create(),
-                        KeyCode::Left => {
-                            if app.cursor_pos > 0 {
-                                app.cursor_pos -= 1;
-                            }
+                        KeyCode::Left if app.cursor_pos > 0 => {
+                            app.cursor_pos -= 1;
                         }
-                        KeyCode::Right => {
-                            if app.cursor_pos < app.create_input.chars().count() {
-                                app.cursor_pos += 1;
-                            }
+                        KeyCode::Right if app.cursor_pos < app.create_input.chars().count() => {
+                            app.cursor_pos += 1;
                         }
                         KeyCode::Backspace => {
                             input_backspace(&mut app.create_input, &mut app.cursor_pos);
@@ -376,18 +480,67 @@ fn run_picker(
                         }
                         _ => {}
                     },
+                    Mode::ContentSearch => match key.code {
+                        KeyCode::Enter => app.confirm_content_search(),
+                        KeyCode::Esc => app.cancel_content_search(),
+                        KeyCode::Left if app.cursor_pos > 0 => {
+                            app.cursor_pos -= 1;
+                        }
+                        KeyCode::Right if app.cursor_pos < app.create_input.chars().count() => {
+                            app.cursor_pos += 1;
+                        }
+                        KeyCode::Backspace => {
+                            input_backspace(&mut app.create_input, &mut app.cursor_pos);
+                        }
+                        KeyCode::Char(c) => {
+                            input_insert(&mut app.create_input, &mut app.cursor_pos, c);
+                        }
+                        _ => {}
+                    },
+                    Mode::ContentResults => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down
+                            if app.content_selected + 1 < app.content_results.len() =>
+                        {
+                            app.content_selected += 1;
+                        }
+                        KeyCode::Char('k') | KeyCode::Up if app.content_selected > 0 => {
+                            app.content_selected -= 1;
+                        }
+                        KeyCode::Enter => app.attach_content_result(),
+                        KeyCode::Char('/') | KeyCode::Char('F') => app.start_content_search(),
+                        KeyCode::Esc | KeyCode::Char('q') => app.close_content_results(),
+                        _ => {}
+                    },
                     Mode::EditingCommand => match key.code {
                         KeyCode::Enter => app.confirm_command(),
                         KeyCode::Esc => app.cancel_command(),
-                        KeyCode::Left => {
-                            if app.cursor_pos > 0 {
-                                app.cursor_pos -= 1;
-                            }
+                        KeyCode::Left if app.cursor_pos > 0 => {
+                            app.cursor_pos -= 1;
                         }
-                        KeyCode::Right => {
-                            if app.cursor_pos < app.create_input.chars().count() {
-                                app.cursor_pos += 1;
-                            }
+                        KeyCode::Right if app.cursor_pos < app.create_input.chars().count() => {
+                            app.cursor_pos += 1;
+                        }
+                        KeyCode::Backspace => {
+                            input_backspace(&mut app.create_input, &mut app.cursor_pos);
+                        }
+                        KeyCode::Char(c) => {
+                            input_insert(&mut app.create_input, &mut app.cursor_pos, c);
+                        }
+                        _ => {}
+                    },
+                    Mode::FinishResult => match key.code {
+                        KeyCode::Enter => app.attach_finish_result(),
+                        KeyCode::Esc | KeyCode::Char('q') => app.close_finish_result(),
+                        _ => {}
+                    },
+                    Mode::EditingTags => match key.code {
+                        KeyCode::Enter => app.confirm_tags(),
+                        KeyCode::Esc => app.cancel_tags(),
+                        KeyCode::Left if app.cursor_pos > 0 => {
+                            app.cursor_pos -= 1;
+                        }
+                        KeyCode::Right if app.cursor_pos < app.create_input.chars().count() => {
+                            app.cursor_pos += 1;
                         }
                         KeyCode::Backspace => {
                             input_backspace(&mut app.create_input, &mut app.cursor_pos);
@@ -397,6 +550,39 @@ fn run_picker(
                         }
                         _ => {}
                     },
+                    Mode::CreatingWorktree => match key.code {
+                        KeyCode::Enter => app.confirm_worktree_create(),
+                        KeyCode::Esc => app.cancel_worktree_create(),
+                        KeyCode::Left if app.cursor_pos > 0 => {
+                            app.cursor_pos -= 1;
+                        }
+                        KeyCode::Right if app.cursor_pos < app.create_input.chars().count() => {
+                            app.cursor_pos += 1;
+                        }
+                        KeyCode::Backspace => {
+                            input_backspace(&mut app.create_input, &mut app.cursor_pos);
+                        }
+                        KeyCode::Char(c) => {
+                            input_insert(&mut app.create_input, &mut app.cursor_pos, c);
+                        }
+                        _ => {}
+                    },
+                    Mode::ConfirmRemoveWorktree => match key.code {
+                        KeyCode::Char('y') => app.confirm_worktree_remove(),
+                        KeyCode::Esc | KeyCode::Char('n') => app.cancel_worktree_remove(),
+                        _ => {}
+                    },
+                    Mode::ConfirmRecreate => match key.code {
+                        KeyCode::Char('y') | KeyCode::Enter => app.confirm_recreate(),
+                        KeyCode::Char('n') | KeyCode::Esc => app.cancel_recreate(),
+                        _ => {}
+                    },
+                    Mode::ConfirmTrust => match key.code {
+                        KeyCode::Char('y') => app.confirm_trust(),
+                        KeyCode::Char('n') => app.skip_trust(),
+                        KeyCode::Esc => app.cancel_trust(),
+                        _ => {}
+                    },
                     Mode::ConfirmPin => match key.code {
                         KeyCode::Char('y') | KeyCode::Enter => app.confirm_pin(),
                         KeyCode::Char('n') | KeyCode::Esc => app.cancel_pin(),
@@ -432,59 +618,97 @@ fn run_picker(
                         _ => {}
                     },
                     Mode::Ordering => match key.code {
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            if app.ordering_selected + 1 < app.ordering_items.len() {
-                                app.ordering_selected += 1;
-                            }
+                        KeyCode::Char('j') | KeyCode::Down if app.ordering_selected + 1 < app.ordering_items.len() => {
+                            app.ordering_selected += 1;
                         }
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            if app.ordering_selected > 0 {
-                                app.ordering_selected -= 1;
-                            }
+                        KeyCode::Char('k') | KeyCode::Up if app.ordering_selected > 0 => {
+                            app.ordering_selected -= 1;
                         }
-                        KeyCode::Char('J') => {
-                            if app.ordering_selected + 1 < app.ordering_items.len() {
-                                app.ordering_items.swap(app.ordering_selected, app.ordering_selected + 1);
-                                app.ordering_selected += 1;
-                            }
+                        KeyCode::Char('J') if app.ordering_selected + 1 < app.ordering_items.len() => {
+                            app.ordering_items.swap(app.ordering_selected, app.ordering_selected + 1);
+                            app.ordering_selected += 1;
                         }
-                        KeyCode::Char('K') => {
-                            if app.ordering_selected > 0 {
-                                app.ordering_items.swap(app.ordering_selected, app.ordering_selected - 1);
-                                app.ordering_selected -= 1;
-                            }
+                        KeyCode::Char('K') if app.ordering_selected > 0 => {
+                            app.ordering_items.swap(app.ordering_selected, app.ordering_selected - 1);
+                            app.ordering_selected -= 1;
                         }
                         KeyCode::Enter => app.confirm_ordering(),
                         KeyCode::Esc => app.cancel_ordering(),
                         _ => {}
                     },
                     Mode::ConstantOrdering => match key.code {
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            if app.ordering_selected + 1 < app.ordering_items.len() {
-                                app.ordering_selected += 1;
-                            }
+                        KeyCode::Char('j') | KeyCode::Down if app.ordering_selected + 1 < app.ordering_items.len() => {
+                            app.ordering_selected += 1;
                         }
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            if app.ordering_selected > 0 {
-                                app.ordering_selected -= 1;
-                            }
+                        KeyCode::Char('k') | KeyCode::Up if app.ordering_selected > 0 => {
+                            app.ordering_selected -= 1;
                         }
-                        KeyCode::Char('J') => {
-                            if app.ordering_selected + 1 < app.ordering_items.len() {
-                                app.ordering_items.swap(app.ordering_selected, app.ordering_selected + 1);
-                                app.ordering_selected += 1;
-                            }
+                        KeyCode::Char('J') if app.ordering_selected + 1 < app.ordering_items.len() => {
+                            app.ordering_items.swap(app.ordering_selected, app.ordering_selected + 1);
+                            app.ordering_selected += 1;
                         }
-                        KeyCode::Char('K') => {
-                            if app.ordering_selected > 0 {
-                                app.ordering_items.swap(app.ordering_selected, app.ordering_selected - 1);
-                                app.ordering_selected -= 1;
-                            }
+                        KeyCode::Char('K') if app.ordering_selected > 0 => {
+                            app.ordering_items.swap(app.ordering_selected, app.ordering_selected - 1);
+                            app.ordering_selected -= 1;
                         }
                         KeyCode::Enter => app.confirm_constant_ordering(),
                         KeyCode::Esc => app.cancel_constant_ordering(),
                         _ => {}
                     },
+                    Mode::RestorePreview => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down
+                            if app.restore_selected + 1 < app.restore_candidates.len() =>
+                        {
+                            app.restore_selected += 1;
+                        }
+                        KeyCode::Char('k') | KeyCode::Up if app.restore_selected > 0 => {
+                            app.restore_selected -= 1;
+                        }
+                        KeyCode::Char(' ') => app.toggle_restore_candidate(),
+                        KeyCode::Char('a') => app.toggle_all_restore_candidates(),
+                        KeyCode::Enter => app.restore_sessions(),
+                        KeyCode::Esc => app.cancel_restore(),
+                        _ => {}
+                    },
+                    Mode::Archives => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down
+                            if app.archive_selected + 1 < app.archives.len() =>
+                        {
+                            app.archive_selected += 1;
+                        }
+                        KeyCode::Char('k') | KeyCode::Up if app.archive_selected > 0 => {
+                            app.archive_selected -= 1;
+                        }
+                        KeyCode::Enter => app.view_archive(),
+                        KeyCode::Char('u') => app.undo_kill(),
+                        KeyCode::Char('x') => app.delete_archive(),
+                        KeyCode::Esc | KeyCode::Char('q') => app.close_archives(),
+                        _ => {}
+                    },
+                    Mode::ArchiveView => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down
+                            if app.archive_scroll + 1 < app.archive_lines.len() =>
+                        {
+                            app.archive_scroll += 1;
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => {
+                            app.archive_scroll = app.archive_scroll.saturating_sub(1);
+                        }
+                        KeyCode::PageDown | KeyCode::Char('d') => {
+                            app.archive_scroll = (app.archive_scroll + 20)
+                                .min(app.archive_lines.len().saturating_sub(1));
+                        }
+                        KeyCode::PageUp | KeyCode::Char('u') => {
+                            app.archive_scroll = app.archive_scroll.saturating_sub(20);
+                        }
+                        KeyCode::Char('g') => app.archive_scroll = 0,
+                        KeyCode::Char('G') => {
+                            app.archive_scroll = app.archive_lines.len().saturating_sub(1);
+                        }
+                        KeyCode::Char('U') => app.undo_kill(),
+                        KeyCode::Esc | KeyCode::Char('q') => app.close_archive_view(),
+                        _ => {}
+                    },
                 },
                 Event::Mouse(mouse) => {
                     match mouse.kind {
@@ -524,14 +748,3 @@ fn run_picker(
     let action = std::mem::replace(&mut app.action, Action::None);
     Ok(action)
 }
-
-/// Disable terminal flow control (Ctrl+S / Ctrl+Q) so Ctrl+S reaches screen.
-fn disable_flow_control() {
-    unsafe {
-        let mut termios: libc::termios = std::mem::zeroed();
-        if libc::tcgetattr(0, &mut termios) == 0 {
-            termios.c_iflag &= !libc::IXON;
-            libc::tcsetattr(0, libc::TCSANOW, &termios);
-        }
-    }
-}
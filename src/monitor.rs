@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::hooks::spawn_detached;
+use crate::paths;
+use crate::screen;
+use crate::statefile;
+
+/// How often watched sessions are polled while the picker is open.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Lines of output kept from a finished command.
+const FINISH_TAIL_LINES: usize = 12;
+
+#[derive(Clone, Debug)]
+pub enum MonitorEvent {
+    /// a window rang the bell
+    Bell,
+    /// output stopped for `silence_secs` after there had been some
+    Silence,
+    /// the command marked with `n` exited
+    Finished(Finished),
+}
+
+impl MonitorEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MonitorEvent::Bell => "bell",
+            MonitorEvent::Silence => "silence",
+            MonitorEvent::Finished(_) => "finished",
+        }
+    }
+}
+
+/// A command that was being waited on and has exited.
+#[derive(Clone, Debug)]
+pub struct Finished {
+    pub command: String,
+    /// from start until the last poll that still saw it running; polls are
+    /// paused while attached, so the time it actually exited isn't known
+    pub elapsed: Duration,
+    /// last lines of the window it ran in
+    pub tail: Vec<String>,
+}
+
+/// Row badge for a watched session.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Badge {
+    Quiet,
+    Activity,
+    Bell,
+    /// waiting for a command to finish
+    Running,
+}
+
+/// A foreground command being waited on.
+struct FinishWatch {
+    pid: u32,
+    command: String,
+    window: Option<u32>,
+    started: Instant,
+    /// when a poll last found it still running
+    last_seen: Instant,
+}
+
+/// What one poll saw in a watched session.
+struct PollResult {
+    pid_name: String,
+    bell: bool,
+    /// hash of every window's visible screen
+    screen_hash: u64,
+}
+
+#[derive(Default)]
+struct Poll {
+    sessions: Vec<PollResult>,
+    /// pid_name -> output tail, for waited-on commands that have exited
+    finished: Vec<(String, Vec<String>)>,
+    /// waited-on commands still running when the process map was taken
+    running: Vec<String>,
+    /// when that was
+    checked_at: Option<Instant>,
+}
+
+struct WatchState {
+    screen_hash: Option<u64>,
+    last_change: Instant,
+    activity: bool,
+    bell: bool,
+    silence_reported: bool,
+}
+
+/// Socket mtime of an unwatched session. Screen touches the socket on
+/// output, so a newer mtime than when the user last looked means activity.
+struct Seen {
+    /// `None` until the next stat, after attach or when first listed
+    baseline: Option<SystemTime>,
+    activity: bool,
+}
+
+/// Watches selected sessions for output, bells and silence. Screen's own
+/// `monitor`/`silence` are switched on too, so an attached display gets
+/// screen's native notices as well. Every other live session gets a
+/// cheaper socket-mtime check, enough for an activity badge.
+pub struct Monitor {
+    silence_secs: u64,
+    /// pid_name -> state
+    watched: HashMap<String, WatchState>,
+    /// pid_name -> socket mtime state, for every live session
+    seen: HashMap<String, Seen>,
+    last_stat: Instant,
+    /// pid_name -> command waited on with `n`
+    finishing: HashMap<String, FinishWatch>,
+    pending: Option<Receiver<Poll>>,
+    last_poll: Instant,
+}
+
+impl Monitor {
+    pub fn new(silence_secs: u64) -> Self {
+        Self {
+            silence_secs,
+            watched: HashMap::new(),
+            seen: HashMap::new(),
+            last_stat: Instant::now(),
+            finishing: HashMap::new(),
+            pending: None,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Start or stop watching a session. Returns whether it is now watched;
+    /// when screen refuses, nothing changes.
+    pub fn toggle(&mut self, pid_name: &str) -> Result<bool, screen::Failure> {
+        let watching = !self.watched.contains_key(pid_name);
+        let silence = if watching { self.silence_secs.to_string() } else { "off".to_string() };
+        let monitor = if watching { "on" } else { "off" };
+        screen::send(pid_name, &["at", "#", "monitor", monitor])?;
+        screen::send(pid_name, &["at", "#", "silence", &silence])?;
+        if let Some(seen) = self.seen.get_mut(pid_name) {
+            seen.baseline = None;
+            seen.activity = false;
+        }
+        if !watching {
+            self.watched.remove(pid_name);
+        } else {
+            self.watched.insert(
+                pid_name.to_string(),
+                WatchState {
+                    screen_hash: None,
+                    last_change: Instant::now(),
+                    activity: false,
+                    bell: false,
+                    silence_reported: false,
+                },
+            );
+        }
+        Ok(watching)
+    }
+
+    /// Start or stop waiting for the session's foreground command to exit.
+    /// Returns the command when waiting starts, or an error when nothing is
+    /// running.
+    pub fn toggle_finish(&mut self, pid_name: &str) -> Result<Option<String>, String> {
+        if self.finishing.remove(pid_name).is_some() {
+            return Ok(None);
+        }
+        let screen_pid = pid_name
+            .split('.')
+            .next()
+            .and_then(|p| p.parse::<u32>().ok())
+            .ok_or_else(|| format!("Bad session id '{pid_name}'"))?;
+        let map = screen::build_process_map();
+        let (pid, command) =
+            screen::foreground_process(&map, screen_pid).ok_or("Nothing is running")?;
+        let window = screen::snapshot_windows(&map, pid_name)
+            .into_iter()
+            .find(|w| w.command.as_deref() == Some(command.as_str()))
+            .map(|w| w.number);
+        let running_for = screen::process_elapsed(pid).unwrap_or(0);
+        let started = Instant::now()
+            .checked_sub(Duration::from_secs(running_for))
+            .unwrap_or_else(Instant::now);
+        self.finishing.insert(
+            pid_name.to_string(),
+            FinishWatch { pid, command: command.clone(), window, started, last_seen: Instant::now() },
+        );
+        Ok(Some(command))
+    }
+
+    pub fn badge(&self, pid_name: &str) -> Option<Badge> {
+        if self.finishing.contains_key(pid_name) {
+            return Some(Badge::Running);
+        }
+        let activity = match self.watched.get(pid_name) {
+            Some(state) if state.bell => return Some(Badge::Bell),
+            Some(state) => state.activity,
+            None => self.seen.get(pid_name)?.activity,
+        };
+        Some(if activity { Badge::Activity } else { Badge::Quiet })
+    }
+
+    /// Forget what was seen — the user is about to look at the session.
+    pub fn clear(&mut self, pid_name: &str) {
+        // Attaching and detaching touch the socket too; take the baseline
+        // again once back in the picker.
+        if let Some(seen) = self.seen.get_mut(pid_name) {
+            seen.baseline = None;
+            seen.activity = false;
+        }
+        if let Some(state) = self.watched.get_mut(pid_name) {
+            state.activity = false;
+            state.bell = false;
+            state.silence_reported = false;
+            state.screen_hash = None;
+        }
+    }
+
+    /// Drop sessions that no longer exist and start tracking new ones.
+    pub fn retain(&mut self, live: &[screen::Session]) {
+        self.seen
+            .retain(|pid_name, _| live.iter().any(|s| &s.pid_name == pid_name));
+        for s in live {
+            self.seen
+                .entry(s.pid_name.clone())
+                .or_insert(Seen { baseline: None, activity: false });
+        }
+        self.watched
+            .retain(|pid_name, _| live.iter().any(|s| &s.pid_name == pid_name));
+        self.finishing
+            .retain(|pid_name, _| live.iter().any(|s| &s.pid_name == pid_name));
+    }
+
+    /// Collect a finished poll and start the next one when it is due.
+    /// Returns `(pid_name, event)` for every bell, silence or finished
+    /// command seen.
+    pub fn tick(&mut self) -> Vec<(String, MonitorEvent)> {
+        if self.last_stat.elapsed() >= POLL_INTERVAL {
+            self.last_stat = Instant::now();
+            self.stat_sockets();
+        }
+        let mut events = Vec::new();
+        if let Some(rx) = &self.pending {
+            match rx.try_recv() {
+                Ok(poll) => {
+                    events = self.apply(poll);
+                    self.pending = None;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        if self.pending.is_none()
+            && !(self.watched.is_empty() && self.finishing.is_empty())
+            && self.last_poll.elapsed() >= POLL_INTERVAL
+        {
+            self.last_poll = Instant::now();
+            let finishing = self
+                .finishing
+                .iter()
+                .map(|(pid_name, f)| (pid_name.clone(), f.pid, f.window))
+                .collect();
+            self.pending = Some(spawn_poll(self.watched.keys().cloned().collect(), finishing));
+        }
+        events
+    }
+
+    /// Compare every unwatched session's socket mtime with its baseline.
+    /// A `stat` per session, cheap enough for the UI thread.
+    fn stat_sockets(&mut self) {
+        for (pid_name, seen) in &mut self.seen {
+            if seen.activity || self.watched.contains_key(pid_name) {
+                continue;
+            }
+            let Some(mtime) = screen::socket_mtime(pid_name) else { continue };
+            match seen.baseline {
+                None => seen.baseline = Some(mtime),
+                Some(baseline) if mtime > baseline => seen.activity = true,
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn apply(&mut self, poll: Poll) -> Vec<(String, MonitorEvent)> {
+        let mut events = Vec::new();
+        if let Some(at) = poll.checked_at {
+            for pid_name in &poll.running {
+                if let Some(f) = self.finishing.get_mut(pid_name) {
+                    f.last_seen = f.last_seen.max(at);
+                }
+            }
+        }
+        for (pid_name, tail) in poll.finished {
+            let Some(f) = self.finishing.remove(&pid_name) else { continue };
+            let elapsed = f.last_seen.saturating_duration_since(f.started);
+            let finished = Finished { command: f.command, elapsed, tail };
+            events.push((pid_name, MonitorEvent::Finished(finished)));
+        }
+        let silence = Duration::from_secs(self.silence_secs);
+        for r in poll.sessions {
+            let Some(state) = self.watched.get_mut(&r.pid_name) else { continue };
+            match state.screen_hash {
+                None => state.last_change = Instant::now(),
+                Some(h) if h != r.screen_hash => {
+                    state.activity = true;
+                    state.last_change = Instant::now();
+                    state.silence_reported = false;
+                }
+                _ => {}
+            }
+            state.screen_hash = Some(r.screen_hash);
+            if r.bell && !state.bell {
+                state.bell = true;
+                events.push((r.pid_name.clone(), MonitorEvent::Bell));
+            }
+            if state.activity && !state.silence_reported && state.last_change.elapsed() >= silence {
+                state.silence_reported = true;
+                events.push((r.pid_name, MonitorEvent::Silence));
+            }
+        }
+        events
+    }
+}
+
+/// Poll every watched session on a background thread: window flags for the
+/// bell, and a hash of each window's visible screen for activity. Waited-on
+/// commands are checked against a fresh process list.
+fn spawn_poll(watched: Vec<String>, finishing: Vec<(String, u32, Option<u32>)>) -> Receiver<Poll> {
+    let (tx, rx) = mpsc::sync_channel(1);
+    std::thread::spawn(move || {
+        let mut poll = Poll {
+            sessions: watched.into_iter().map(poll_session).collect(),
+            finished: Vec::new(),
+            running: Vec::new(),
+            checked_at: None,
+        };
+        if !finishing.is_empty() {
+            poll.checked_at = Some(Instant::now());
+            let map = screen::build_process_map();
+            for (pid_name, pid, window) in finishing {
+                if map.contains(pid) {
+                    poll.running.push(pid_name);
+                } else {
+                    let tail = capture_tail(&pid_name, window);
+                    poll.finished.push((pid_name, tail));
+                }
+            }
+        }
+        let _ = tx.send(poll);
+    });
+    rx
+}
+
+/// Last non-blank lines on the window's screen.
+fn capture_tail(pid_name: &str, window: Option<u32>) -> Vec<String> {
+    let Some(window) = window.or_else(|| screen::list_windows(pid_name).first().map(|(n, _)| *n))
+    else {
+        return Vec::new();
+    };
+    let path = paths::scratch_dir()
+        .join(format!("scrn-{}-{pid_name}-{window}.tail", std::process::id()));
+    let text = if screen::hardcopy(pid_name, window, &path, false) {
+        std::fs::read(&path)
+            .map(|b| String::from_utf8_lossy(&b).into_owned())
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let _ = std::fs::remove_file(&path);
+    let lines: Vec<String> = text.trim_end().lines().map(|l| l.trim_end().to_string()).collect();
+    let skip = lines.len().saturating_sub(FINISH_TAIL_LINES);
+    lines.into_iter().skip(skip).collect()
+}
+
+/// `45s`, `3m12s`, `2h05m`, `3d04h`.
+pub fn format_elapsed(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if secs >= 86400 {
+        format!("{}d{:02}h", secs / 86400, (secs % 86400) / 3600)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+fn poll_session(pid_name: String) -> PollResult {
+    let windows = screen::list_windows(&pid_name);
+    let bell = windows.iter().any(|(_, flags)| flags.contains('!'));
+    let mut screens = Vec::new();
+    let tmp = paths::scratch_dir();
+    for (number, _) in &windows {
+        let path = tmp.join(format!("scrn-{}-{pid_name}-{number}.mon", std::process::id()));
+        if screen::hardcopy(&pid_name, *number, &path, false) {
+            if let Ok(bytes) = std::fs::read(&path) {
+                screens.extend_from_slice(&bytes);
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    PollResult {
+        pid_name,
+        bell,
+        screen_hash: statefile::fnv1a(&screens),
+    }
+}
+
+/// Raise a desktop notification and/or run the configured hook for an event.
+/// Both run detached so a slow notifier can't stall the picker.
+pub fn notify(desktop: bool, hook: Option<&str>, session: &str, event: &str, message: &str) {
+    if desktop {
+        let cmd = if cfg!(target_os = "macos") {
+            let script = format!(
+                "display notification \"{}\" with title \"scrn\"",
+                message.replace(['"', '\\'], "")
+            );
+            let mut c = Command::new("osascript");
+            c.args(["-e", &script]);
+            c
+        } else {
+            let mut c = Command::new("notify-send");
+            c.args(["scrn", message]);
+            c
+        };
+        spawn_detached(cmd, "notification");
+    }
+    if let Some(hook) = hook {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", hook])
+            .env("SCRN_EVENT", event)
+            .env("SCRN_SESSION", session)
+            .env("SCRN_MESSAGE", message);
+        spawn_detached(cmd, &format!("notify hook '{hook}'"));
+    }
+}
@@ -0,0 +1,105 @@
+use std::ffi::CStr;
+use std::fs;
+use std::path::PathBuf;
+
+/// `$HOME`, or the passwd entry when it is unset (cron, `env -i`).
+pub fn home_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("HOME").filter(|h| !h.is_empty()) {
+        return PathBuf::from(home);
+    }
+    // SAFETY: getpwuid returns a pointer into static storage or null; the
+    // directory is copied out before anything else can call it.
+    unsafe {
+        let pw = libc::getpwuid(libc::getuid());
+        if !pw.is_null() && !(*pw).pw_dir.is_null() {
+            let dir = CStr::from_ptr((*pw).pw_dir).to_string_lossy().into_owned();
+            if !dir.is_empty() {
+                return PathBuf::from(dir);
+            }
+        }
+    }
+    PathBuf::from("/")
+}
+
+/// `$<var>/scrn`, or `~/<fallback>/scrn` when the variable is unset or
+/// relative (the XDG spec says to ignore relative paths).
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir.join("scrn"),
+        _ => home_dir().join(fallback).join("scrn"),
+    }
+}
+
+/// `config.toml`. Nothing scrn writes lives here, so the directory can be
+/// synced with dotfiles.
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// The state store, log, event log and archives.
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// The workspace tree cache.
+pub fn cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// The generated screenrc and scratch hardcopy files. Falls back to the
+/// cache directory when there is no `$XDG_RUNTIME_DIR`.
+pub fn runtime_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir.join("scrn"),
+        _ => cache_dir(),
+    }
+}
+
+/// The runtime directory, created, for hardcopy files read back at once.
+pub fn scratch_dir() -> PathBuf {
+    let dir = runtime_dir();
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// Where screen keeps its sockets: `$SCREENDIR`, or `~/.screen`.
+pub fn socket_dir() -> PathBuf {
+    match std::env::var_os("SCREENDIR").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => home_dir().join(".screen"),
+    }
+}
+
+/// Where everything lived before the XDG split: `~/.config/scrn`.
+pub fn legacy_dir() -> PathBuf {
+    home_dir().join(".config").join("scrn")
+}
+
+pub fn config_file() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+pub fn log_file() -> PathBuf {
+    state_dir().join("scrn.log")
+}
+
+pub fn events_file() -> PathBuf {
+    state_dir().join("events")
+}
+
+/// `scrn paths`: every location scrn reads or writes, one per line.
+pub fn print() {
+    let rows = [
+        ("config", config_file()),
+        ("state", state_dir().join("state")),
+        ("log", log_file()),
+        ("events", events_file()),
+        ("archive", state_dir().join("archive")),
+        ("cache", cache_dir()),
+        ("runtime", runtime_dir()),
+        ("sockets", socket_dir()),
+    ];
+    for (label, path) in rows {
+        println!("{label:<8} {}", path.display());
+    }
+}
@@ -0,0 +1,391 @@
+#[derive(Clone, Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class { items: Vec<ClassItem>, negated: bool },
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat { node: Box<Node>, min: usize, max: Option<usize> },
+}
+
+#[derive(Clone, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+/// A small regex matcher for `/re/` search terms. Patterns compile to a
+/// program that runs in time linear in the text, so a pathological pattern
+/// like `(a+)+$` can't stall the UI thread.
+///
+/// Supports literals, `.`, `^`, `$`, classes (`[a-z]`, `[^0-9]`), the escapes
+/// `\d \w \s` (and their negations), groups with `|` alternation, and the
+/// quantifiers `*`, `+`, `?`. Matching is case-insensitive.
+#[derive(Debug)]
+pub struct Regex {
+    prog: Vec<Inst>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut pos = 0;
+        let alts = parse_alternation(&chars, &mut pos)?;
+        if pos < chars.len() {
+            return Err("unmatched ')'".to_string());
+        }
+        let mut prog = Vec::new();
+        compile_alts(alts, &mut prog);
+        prog.push(Inst::Match);
+        Ok(Self { prog })
+    }
+
+    /// Char range of the leftmost match in `text`.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let chars: Vec<char> = text.chars().collect();
+        Vm { prog: &self.prog, text: &chars, seen: vec![0; self.prog.len()] }.find()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+}
+
+fn parse_alternation(chars: &[char], pos: &mut usize) -> Result<Vec<Vec<Node>>, String> {
+    let mut alts = vec![parse_sequence(chars, pos)?];
+    while *pos < chars.len() && chars[*pos] == '|' {
+        *pos += 1;
+        alts.push(parse_sequence(chars, pos)?);
+    }
+    Ok(alts)
+}
+
+fn parse_sequence(chars: &[char], pos: &mut usize) -> Result<Vec<Node>, String> {
+    let mut seq = Vec::new();
+    while *pos < chars.len() && chars[*pos] != '|' && chars[*pos] != ')' {
+        let atom = parse_atom(chars, pos)?;
+        let quantified = match chars.get(*pos) {
+            Some('*') => Some((0, None)),
+            Some('+') => Some((1, None)),
+            Some('?') => Some((0, Some(1))),
+            _ => None,
+        };
+        if let Some((min, max)) = quantified {
+            *pos += 1;
+            if matches!(atom, Node::Start | Node::End) {
+                return Err("nothing to repeat".to_string());
+            }
+            seq.push(Node::Repeat { node: Box::new(atom), min, max });
+        } else {
+            seq.push(atom);
+        }
+    }
+    Ok(seq)
+}
+
+fn parse_atom(chars: &[char], pos: &mut usize) -> Result<Node, String> {
+    let c = chars[*pos];
+    *pos += 1;
+    match c {
+        '.' => Ok(Node::Any),
+        '^' => Ok(Node::Start),
+        '$' => Ok(Node::End),
+        '*' | '+' | '?' => Err(format!("nothing to repeat before '{c}'")),
+        '(' => {
+            let alts = parse_alternation(chars, pos)?;
+            if chars.get(*pos) != Some(&')') {
+                return Err("unclosed '('".to_string());
+            }
+            *pos += 1;
+            Ok(Node::Group(alts))
+        }
+        '[' => parse_class(chars, pos),
+        '\\' => {
+            let e = *chars.get(*pos).ok_or("trailing '\\'")?;
+            *pos += 1;
+            Ok(match escape_class(e) {
+                Some(item) => Node::Class { items: vec![item], negated: false },
+                None => Node::Char(e),
+            })
+        }
+        _ => Ok(Node::Char(c)),
+    }
+}
+
+fn escape_class(e: char) -> Option<ClassItem> {
+    match e {
+        'd' => Some(ClassItem::Digit(true)),
+        'D' => Some(ClassItem::Digit(false)),
+        'w' => Some(ClassItem::Word(true)),
+        'W' => Some(ClassItem::Word(false)),
+        's' => Some(ClassItem::Space(true)),
+        'S' => Some(ClassItem::Space(false)),
+        _ => None,
+    }
+}
+
+fn parse_class(chars: &[char], pos: &mut usize) -> Result<Node, String> {
+    let negated = chars.get(*pos) == Some(&'^');
+    if negated {
+        *pos += 1;
+    }
+    let mut items = Vec::new();
+    let mut first = true;
+    loop {
+        let Some(&c) = chars.get(*pos) else {
+            return Err("unclosed '['".to_string());
+        };
+        *pos += 1;
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+        let lo = if c == '\\' {
+            let e = *chars.get(*pos).ok_or("trailing '\\'")?;
+            *pos += 1;
+            if let Some(item) = escape_class(e) {
+                items.push(item);
+                continue;
+            }
+            e
+        } else {
+            c
+        };
+        if chars.get(*pos) == Some(&'-') && chars.get(*pos + 1).is_some_and(|&n| n != ']') {
+            let hi = chars[*pos + 1];
+            *pos += 2;
+            if hi < lo {
+                return Err(format!("bad range {lo}-{hi}"));
+            }
+            items.push(ClassItem::Range(lo, hi));
+        } else {
+            items.push(ClassItem::Range(lo, lo));
+        }
+    }
+    Ok(Node::Class { items, negated })
+}
+
+fn chars_eq_ci(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+fn class_matches(items: &[ClassItem], c: char) -> bool {
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let upper = c.to_uppercase().next().unwrap_or(c);
+    items.iter().any(|item| match *item {
+        ClassItem::Range(lo, hi) => [c, lower, upper].iter().any(|&x| lo <= x && x <= hi),
+        ClassItem::Digit(want) => c.is_ascii_digit() == want,
+        ClassItem::Word(want) => (c.is_alphanumeric() || c == '_') == want,
+        ClassItem::Space(want) => c.is_whitespace() == want,
+    })
+}
+
+/// One instruction of the compiled program. `Split` prefers its first
+/// target, which is what makes quantifiers greedy.
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class { items: Vec<ClassItem>, negated: bool },
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+fn compile_alts(alts: Vec<Vec<Node>>, prog: &mut Vec<Inst>) {
+    let count = alts.len();
+    let mut jumps = Vec::new();
+    for (n, alt) in alts.into_iter().enumerate() {
+        if n + 1 < count {
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            compile_seq(alt, prog);
+            jumps.push(prog.len());
+            prog.push(Inst::Jmp(0));
+            let next = prog.len();
+            prog[split] = Inst::Split(split + 1, next);
+        } else {
+            compile_seq(alt, prog);
+        }
+    }
+    let end = prog.len();
+    for j in jumps {
+        prog[j] = Inst::Jmp(end);
+    }
+}
+
+fn compile_seq(nodes: Vec<Node>, prog: &mut Vec<Inst>) {
+    for node in nodes {
+        compile_node(node, prog);
+    }
+}
+
+fn compile_node(node: Node, prog: &mut Vec<Inst>) {
+    match node {
+        Node::Char(c) => prog.push(Inst::Char(c)),
+        Node::Any => prog.push(Inst::Any),
+        Node::Class { items, negated } => prog.push(Inst::Class { items, negated }),
+        Node::Start => prog.push(Inst::Start),
+        Node::End => prog.push(Inst::End),
+        Node::Group(alts) => compile_alts(alts, prog),
+        Node::Repeat { node, min, max } => compile_repeat(*node, min, max, prog),
+    }
+}
+
+/// `min` copies of the body, then a loop (`*`, `+`) or `max - min`
+/// optional copies (`?`).
+fn compile_repeat(node: Node, min: usize, max: Option<usize>, prog: &mut Vec<Inst>) {
+    for _ in 0..min {
+        compile_node(node.clone(), prog);
+    }
+    match max {
+        None => {
+            // L: split body, out; body; jmp L
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            compile_node(node, prog);
+            prog.push(Inst::Jmp(split));
+            let out = prog.len();
+            prog[split] = Inst::Split(split + 1, out);
+        }
+        Some(max) => {
+            let mut splits = Vec::new();
+            for _ in min..max {
+                splits.push(prog.len());
+                prog.push(Inst::Split(0, 0));
+                compile_node(node.clone(), prog);
+            }
+            let out = prog.len();
+            for split in splits {
+                prog[split] = Inst::Split(split + 1, out);
+            }
+        }
+    }
+}
+
+/// Runs a program over one text. Every thread advances in lockstep (a Pike
+/// VM), so matching is linear in the text and never backtracks.
+struct Vm<'a> {
+    prog: &'a [Inst],
+    text: &'a [char],
+    /// Per instruction, the last step it was queued in, so each is queued
+    /// at most once per step. Steps start at 1 so the zeroed array is unseen.
+    seen: Vec<usize>,
+}
+
+impl Vm<'_> {
+    /// Follow `pc` through jumps, splits and assertions at `pos`, queueing the
+    /// instructions that consume a char (or match) in priority order.
+    fn add_thread(&mut self, list: &mut Vec<(usize, usize)>, pc: usize, start: usize, pos: usize) {
+        let step = pos + 1;
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if self.seen[pc] == step {
+                continue;
+            }
+            self.seen[pc] = step;
+            match self.prog[pc] {
+                Inst::Jmp(to) => stack.push(to),
+                Inst::Split(a, b) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                Inst::Start if pos == 0 => stack.push(pc + 1),
+                Inst::End if pos == self.text.len() => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                _ => list.push((pc, start)),
+            }
+        }
+    }
+
+    /// Char range of the leftmost match, preferring earlier alternatives and
+    /// longer repetitions the way a backtracking matcher would.
+    fn find(&mut self) -> Option<(usize, usize)> {
+        let text = self.text;
+        let mut clist: Vec<(usize, usize)> = Vec::new();
+        let mut nlist: Vec<(usize, usize)> = Vec::new();
+        let mut found = None;
+
+        for pos in 0..=text.len() {
+            if found.is_none() {
+                // A new attempt starting here ranks below every earlier one.
+                self.add_thread(&mut clist, 0, pos, pos);
+            }
+            if clist.is_empty() {
+                break;
+            }
+            for &(pc, start) in &clist {
+                let consumes = match &self.prog[pc] {
+                    Inst::Match => {
+                        found = Some((start, pos));
+                        // Everything after this thread has lower priority.
+                        break;
+                    }
+                    Inst::Char(c) => pos < text.len() && chars_eq_ci(text[pos], *c),
+                    Inst::Any => pos < text.len(),
+                    Inst::Class { items, negated } => {
+                        pos < text.len() && class_matches(items, text[pos]) != *negated
+                    }
+                    _ => false,
+                };
+                if consumes {
+                    self.add_thread(&mut nlist, pc + 1, start, pos + 1);
+                }
+            }
+            std::mem::swap(&mut clist, &mut nlist);
+            nlist.clear();
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        Regex::new(pattern).unwrap().find(text)
+    }
+
+    #[test]
+    fn matches_leftmost_first() {
+        assert_eq!(find("b+", "abbbc"), Some((1, 4)));
+        assert_eq!(find("a|ab", "ab"), Some((0, 1)));
+        assert_eq!(find("(a|ab)c", "abc"), Some((0, 3)));
+        assert_eq!(find("x?y", "zy"), Some((1, 2)));
+        assert_eq!(find("a*", "bbb"), Some((0, 0)));
+        assert_eq!(find("^api$", "api"), Some((0, 3)));
+        assert_eq!(find("^api$", "my-api"), None);
+        assert_eq!(find("(a*)*b", "aaab"), Some((0, 4)));
+    }
+
+    #[test]
+    fn classes_escapes_and_case() {
+        assert_eq!(find("[a-c]+", "xxBCAx"), Some((2, 5)));
+        assert_eq!(find("[^0-9]", "12a"), Some((2, 3)));
+        assert_eq!(find("\\d+\\s\\w", "v 12 x"), Some((2, 6)));
+        assert_eq!(find("\\.", "a.b"), Some((1, 2)));
+        assert_eq!(find("API", "my-api"), Some((3, 6)));
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+        for bad in ["(a", "a)", "[ab", "*a", "^*", "[z-a]", "a\\"] {
+            assert!(Regex::new(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn nested_quantifiers_stay_linear() {
+        let text = format!("{}!", "a".repeat(5000));
+        let started = std::time::Instant::now();
+        assert!(!Regex::new("(a+)+$").unwrap().is_match(&text));
+        assert!(Regex::new("(a|aa)*!").unwrap().is_match(&text));
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::app::fuzzy_match;
+use crate::pattern::Regex;
+use crate::screen::{Session, SessionState};
+
+/// A parsed search bar query. Terms are whitespace separated and all must
+/// match:
+///
+/// - `foo` fuzzy-matches the name (`-foo` excludes names containing `foo`)
+/// - `/re.*gex/` matches the name against a regex
+/// - `is:busy|attached|detached|open|pinned|constant`
+/// - `tag:deploy`, `dir:work/`
+/// - `idle:>2d`, `idle:<30m` (units s, m, h, d, w)
+///
+/// Any term can be negated with a leading `-`.
+#[derive(Debug, Default)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+struct Term {
+    negated: bool,
+    kind: TermKind,
+}
+
+#[derive(Debug)]
+enum TermKind {
+    Text(String),
+    Regex(Regex),
+    Is(Flag),
+    Tag(String),
+    Dir(String),
+    IdleOver(u64),
+    IdleUnder(u64),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Flag {
+    Busy,
+    Attached,
+    Detached,
+    Open,
+    Pinned,
+    Constant,
+}
+
+/// Everything a query can look at for one row of the picker.
+pub struct Subject<'a> {
+    pub name: &'a str,
+    pub session: Option<&'a Session>,
+    pub busy: bool,
+    pub pinned: bool,
+    pub constant: bool,
+    pub tags: &'a [String],
+    /// workspace-relative repo path, or the session's working directory
+    pub dir: Option<&'a str>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for token in tokenize(input)? {
+            let (negated, body) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, token.as_str()),
+            };
+            terms.push(Term { negated, kind: parse_term(body)? });
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, subject: &Subject) -> bool {
+        self.terms.iter().all(|t| t.matches(subject) != t.negated)
+    }
+
+    /// Ranking score for `name`: the sum of its positive text-term fuzzy
+    /// scores, saturating so many unmatched terms can't overflow. Queries
+    /// with only filter terms score everything equally.
+    pub fn score(&self, name: &str) -> i32 {
+        self.terms
+            .iter()
+            .filter(|t| !t.negated)
+            .map(|t| match &t.kind {
+                TermKind::Text(text) => fuzzy_match(name, text).map(|(_, s)| s).unwrap_or(i32::MIN / 8),
+                TermKind::Regex(re) if re.is_match(name) => 10000,
+                _ => 0,
+            })
+            .fold(0, i32::saturating_add)
+    }
+
+    /// Char positions in `name` to highlight for positive text and regex terms.
+    pub fn highlight(&self, name: &str) -> Vec<usize> {
+        let mut positions = Vec::new();
+        for t in self.terms.iter().filter(|t| !t.negated) {
+            match &t.kind {
+                TermKind::Text(text) => {
+                    if let Some((p, _)) = fuzzy_match(name, text) {
+                        positions.extend(p);
+                    }
+                }
+                TermKind::Regex(re) => {
+                    if let Some((start, end)) = re.find(name) {
+                        positions.extend(start..end);
+                    }
+                }
+                _ => {}
+            }
+        }
+        positions
+    }
+}
+
+impl Term {
+    fn matches(&self, s: &Subject) -> bool {
+        match &self.kind {
+            TermKind::Text(text) if self.negated => contains_ci(s.name, text),
+            TermKind::Text(text) => {
+                fuzzy_match(s.name, text).is_some()
+                    || s.session.is_some_and(|sess| fuzzy_match(&sess.pid_name, text).is_some())
+            }
+            TermKind::Regex(re) => re.is_match(s.name),
+            TermKind::Is(flag) => match flag {
+                Flag::Busy => s.busy,
+                Flag::Attached => s.session.is_some_and(|x| matches!(x.state, SessionState::Attached)),
+                Flag::Detached => s.session.is_some_and(|x| matches!(x.state, SessionState::Detached)),
+                Flag::Open => s.session.is_some_and(|x| !x.pid_name.is_empty()),
+                Flag::Pinned => s.pinned,
+                Flag::Constant => s.constant,
+            },
+            TermKind::Tag(tag) => s.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            TermKind::Dir(dir) => s.dir.is_some_and(|d| contains_ci(d, dir)),
+            TermKind::IdleOver(secs) => idle_secs(s).is_some_and(|i| i > *secs),
+            TermKind::IdleUnder(secs) => idle_secs(s).is_some_and(|i| i < *secs),
+        }
+    }
+}
+
+fn idle_secs(s: &Subject) -> Option<u64> {
+    s.session.and_then(|x| x.idle_secs)
+}
+
+fn contains_ci(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Split on whitespace, keeping `/regex with spaces/` in one token.
+fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+        if c == '-' {
+            token.push(c);
+            chars.next();
+        }
+        if chars.peek() == Some(&'/') {
+            token.push('/');
+            chars.next();
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                token.push(c);
+                if c == '\\' {
+                    if let Some(n) = chars.next() {
+                        token.push(n);
+                    }
+                } else if c == '/' {
+                    closed = true;
+                    break;
+                }
+            }
+            if !closed {
+                return Err("unterminated /regex/".to_string());
+            }
+        }
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            token.push(c);
+            chars.next();
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(body: &str) -> Result<TermKind, String> {
+    if let Some(rest) = body.strip_prefix('/') {
+        let pattern = rest
+            .strip_suffix('/')
+            .ok_or_else(|| format!("text after /regex/ in '{body}'"))?;
+        return Regex::new(pattern)
+            .map(TermKind::Regex)
+            .map_err(|e| format!("bad regex /{pattern}/: {e}"));
+    }
+    let Some((field, value)) = body.split_once(':') else {
+        return Ok(TermKind::Text(body.to_string()));
+    };
+    match field {
+        "is" => {
+            let flag = match value {
+                "busy" => Flag::Busy,
+                "attached" => Flag::Attached,
+                "detached" => Flag::Detached,
+                "open" => Flag::Open,
+                "pinned" => Flag::Pinned,
+                "constant" => Flag::Constant,
+                _ => {
+                    return Err(format!(
+                        "unknown is:{value} (busy, attached, detached, open, pinned, constant)"
+                    ))
+                }
+            };
+            Ok(TermKind::Is(flag))
+        }
+        "tag" if !value.is_empty() => Ok(TermKind::Tag(value.to_string())),
+        "dir" if !value.is_empty() => Ok(TermKind::Dir(value.to_string())),
+        "idle" => {
+            let (under, spec) = match value.chars().next() {
+                Some('<') => (true, &value[1..]),
+                Some('>') => (false, &value[1..]),
+                _ => (false, value),
+            };
+            let secs = parse_duration(spec).ok_or_else(|| format!("bad duration in idle:{value}"))?;
+            Ok(if under { TermKind::IdleUnder(secs) } else { TermKind::IdleOver(secs) })
+        }
+        "tag" | "dir" => Err(format!("{field}: needs a value")),
+        // Not a known field — treat `a:b` as plain text
+        _ => Ok(TermKind::Text(body.to_string())),
+    }
+}
+
+/// `90`, `30s`, `15m`, `2h`, `3d`, `1w` → seconds. `None` on overflow.
+pub fn parse_duration(spec: &str) -> Option<u64> {
+    let digits: String = spec.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let n: u64 = digits.parse().ok()?;
+    let mult = match &spec[digits.len()..] {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return None,
+    };
+    n.checked_mul(mult)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject<'a>(name: &'a str, tags: &'a [String]) -> Subject<'a> {
+        Subject { name, session: None, busy: false, pinned: false, constant: false, tags, dir: Some("work/api") }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("15m"), Some(900));
+        assert_eq!(parse_duration("1w"), Some(7 * 86400));
+        assert_eq!(parse_duration("3x"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("99999999999999999999"), None);
+        assert_eq!(parse_duration("18446744073709551615w"), None);
+    }
+
+    #[test]
+    fn parses_terms() {
+        assert!(Query::parse("").unwrap().is_empty());
+        assert!(Query::parse("is:bogus").is_err());
+        assert!(Query::parse("tag:").is_err());
+        assert!(Query::parse("/unterminated").is_err());
+        assert!(Query::parse("/(a/").is_err());
+        assert!(Query::parse("idle:>99999999999999999999d").is_err());
+        // Unknown fields are plain text.
+        let q = Query::parse("http://x").unwrap();
+        assert!(matches!(q.terms[0].kind, TermKind::Text(_)));
+        let q = Query::parse("-/a b/ idle:<30m").unwrap();
+        assert_eq!(q.terms.len(), 2);
+        assert!(q.terms[0].negated);
+        assert!(matches!(q.terms[1].kind, TermKind::IdleUnder(1800)));
+    }
+
+    #[test]
+    fn matches_fields_and_negation() {
+        let tags = vec!["deploy".to_string()];
+        let s = subject("api-server", &tags);
+        assert!(Query::parse("api tag:DEPLOY dir:work").unwrap().matches(&s));
+        assert!(!Query::parse("-api").unwrap().matches(&s));
+        assert!(Query::parse("-tag:ops /^api-/").unwrap().matches(&s));
+        assert!(!Query::parse("is:pinned").unwrap().matches(&s));
+        // No session, so idle filters never match.
+        assert!(!Query::parse("idle:>0").unwrap().matches(&s));
+    }
+
+    #[test]
+    fn score_saturates() {
+        let q = Query::parse(&vec!["zzz"; 20].join(" ")).unwrap();
+        assert_eq!(q.score("api"), i32::MIN);
+        let q = Query::parse("/api/ -x idle:>1m").unwrap();
+        assert_eq!(q.score("api"), 10000);
+        assert_eq!(Query::parse("is:busy").unwrap().score("api"), 0);
+    }
+}
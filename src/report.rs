@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+use crate::query::parse_duration;
+use crate::state::{escape, unescape};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Identifies this scrn process in the event log: `<pid>-<start time>`, so
+/// a later instance that reuses the PID isn't mistaken for this one.
+fn instance() -> &'static str {
+    static INSTANCE: OnceLock<String> = OnceLock::new();
+    INSTANCE.get_or_init(|| format!("{}-{}", std::process::id(), now()))
+}
+
+/// Append an attach/detach event:
+/// `<unix ts>\t<kind>\t<name>\t<repo dir>\t<instance>`, with fields escaped
+/// like the state file. `dir` is the repo's workspace-relative path, empty
+/// outside the workspace. Each line goes out in one `write`, so records
+/// from concurrent pickers don't interleave.
+pub fn log_event(kind: &str, name: &str, dir: &str) {
+    let path = paths::events_file();
+    let _ = std::fs::create_dir_all(path.parent().unwrap());
+    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(&path) {
+        let line = format!("{}\t{kind}\t{}\t{}\t{}\n", now(), escape(name), escape(dir), instance());
+        let _ = f.write_all(line.as_bytes());
+    }
+}
+
+struct Event {
+    ts: u64,
+    attach: bool,
+    name: String,
+    dir: String,
+    /// Empty for records written before instances were logged.
+    instance: String,
+}
+
+fn load_events() -> Vec<Event> {
+    let Ok(contents) = std::fs::read_to_string(paths::events_file()) else {
+        return Vec::new();
+    };
+    parse_events(&contents)
+}
+
+fn parse_events(contents: &str) -> Vec<Event> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let ts = fields.next()?.parse().ok()?;
+            let attach = match fields.next()? {
+                "attach" => true,
+                "detach" => false,
+                _ => return None,
+            };
+            let name = unescape(fields.next()?);
+            let dir = unescape(fields.next().unwrap_or(""));
+            let instance = fields.next().unwrap_or("").to_string();
+            Some(Event { ts, attach, name, dir, instance })
+        })
+        .collect()
+}
+
+/// One attached stretch of time.
+struct Span {
+    name: String,
+    dir: String,
+    start: u64,
+    end: u64,
+}
+
+/// Pair every attach with the next event from the same scrn instance. One
+/// instance attaches one session at a time, so a second attach without a
+/// detach (the session was left some other way) closes the first one at
+/// that point. Several pickers can be attached at once; their events
+/// interleave in the file but are paired separately.
+fn spans(events: &[Event]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut open: HashMap<&str, &Event> = HashMap::new();
+    for e in events {
+        if let Some(start) = open.remove(e.instance.as_str()) {
+            spans.push(Span {
+                name: start.name.clone(),
+                dir: start.dir.clone(),
+                start: start.ts,
+                end: e.ts.max(start.ts),
+            });
+        }
+        if e.attach {
+            open.insert(&e.instance, e);
+        }
+    }
+    spans
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GroupBy {
+    Repo,
+    Dir,
+    Tag,
+}
+
+#[derive(Default)]
+struct Row {
+    secs: u64,
+    attaches: usize,
+}
+
+/// `scrn report [--since 7d] [--by repo|dir|tag] [--csv]`: time spent
+/// attached per session, directory or tag.
+pub fn run(args: &[String], tags: &HashMap<String, Vec<String>>) -> Result<(), String> {
+    let mut since = 7 * 86400;
+    let mut by = GroupBy::Repo;
+    let mut csv = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--since" => {
+                let v = args.get(i + 1).ok_or("--since needs a duration, e.g. 7d")?;
+                since = parse_duration(v).ok_or_else(|| format!("bad duration '{v}'"))?;
+                i += 1;
+            }
+            "--by" => {
+                by = match args.get(i + 1).map(|s| s.as_str()) {
+                    Some("repo") => GroupBy::Repo,
+                    Some("dir") => GroupBy::Dir,
+                    Some("tag") => GroupBy::Tag,
+                    _ => return Err("--by takes repo, dir or tag".to_string()),
+                };
+                i += 1;
+            }
+            "--csv" => csv = true,
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+        i += 1;
+    }
+
+    let cutoff = now().saturating_sub(since);
+    let mut rows: HashMap<String, Row> = HashMap::new();
+    for span in spans(&load_events()) {
+        if span.end <= cutoff {
+            continue;
+        }
+        let secs = span.end - span.start.max(cutoff);
+        let keys: Vec<String> = match by {
+            GroupBy::Repo => vec![span.name.clone()],
+            GroupBy::Dir => {
+                let dir = span.dir.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+                vec![if span.dir.is_empty() {
+                    "(outside workspace)".to_string()
+                } else if dir.is_empty() {
+                    "(workspace root)".to_string()
+                } else {
+                    dir.to_string()
+                }]
+            }
+            GroupBy::Tag => match tags.get(&span.name) {
+                Some(t) if !t.is_empty() => t.clone(),
+                _ => vec!["(untagged)".to_string()],
+            },
+        };
+        for key in keys {
+            let row = rows.entry(key).or_default();
+            row.secs += secs;
+            row.attaches += 1;
+        }
+    }
+
+    let mut rows: Vec<(String, Row)> = rows.into_iter().collect();
+    rows.sort_by(|a, b| b.1.secs.cmp(&a.1.secs).then(a.0.cmp(&b.0)));
+
+    if csv {
+        println!("name,seconds,hours,attaches");
+        for (key, row) in &rows {
+            println!(
+                "{},{},{:.2},{}",
+                csv_field(key),
+                row.secs,
+                row.secs as f64 / 3600.0,
+                row.attaches
+            );
+        }
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No attached time recorded in the last {}.", format_span(since));
+        return Ok(());
+    }
+    let width = rows.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0).max(5);
+    for (key, row) in &rows {
+        println!("{key:<width$}  {:>8}  {:>4}x", format_hm(row.secs), row.attaches);
+    }
+    // Tags can count the same time twice, so a total would be misleading
+    if by != GroupBy::Tag {
+        let total: u64 = rows.iter().map(|(_, r)| r.secs).sum();
+        println!("{:<width$}  {:>8}", "total", format_hm(total));
+    }
+    Ok(())
+}
+
+fn format_hm(secs: u64) -> String {
+    format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+}
+
+fn format_span(secs: u64) -> String {
+    if secs % 86400 == 0 {
+        format!("{}d", secs / 86400)
+    } else {
+        format_hm(secs)
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(ts: u64, attach: bool, name: &str, instance: &str) -> Event {
+        Event { ts, attach, name: name.to_string(), dir: String::new(), instance: instance.to_string() }
+    }
+
+    #[test]
+    fn pairs_events_per_instance() {
+        // Two pickers: A attaches api at 0, B attaches web at 10, A detaches
+        // at 30, B detaches at 50.
+        let events = [
+            event(0, true, "api", "1-0"),
+            event(10, true, "web", "2-5"),
+            event(30, false, "api", "1-0"),
+            event(50, false, "web", "2-5"),
+        ];
+        let got: Vec<(String, u64)> = spans(&events).into_iter().map(|s| (s.name, s.end - s.start)).collect();
+        assert_eq!(got, [("api".to_string(), 30), ("web".to_string(), 40)]);
+    }
+
+    #[test]
+    fn parses_escaped_and_legacy_records() {
+        let contents = format!(
+            "5\tattach\t{}\t{}\t7-1\n9\tdetach\tapi\twork/api\nbogus\n",
+            escape("a\tb"),
+            escape("dir\nx")
+        );
+        let events = parse_events(&contents);
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].name.as_str(), events[0].dir.as_str()), ("a\tb", "dir\nx"));
+        assert_eq!(events[0].instance, "7-1");
+        assert_eq!((events[1].name.as_str(), events[1].instance.as_str()), ("api", ""));
+    }
+}
@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::SystemTime;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::logging::{self, Logged};
+use crate::paths;
+use crate::statefile;
 
 extern crate libc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SessionState {
     Attached,
     Detached,
@@ -25,18 +29,27 @@ pub struct Session {
     pub idle_secs: Option<u64>,
 }
 
+/// One window of a running session, captured so it can be rebuilt on restore.
+#[derive(Clone, Debug, Default)]
+pub struct WindowInfo {
+    pub number: u32,
+    pub title: String,
+    pub cwd: Option<PathBuf>,
+    /// Foreground (non-shell) command line, if one was running.
+    pub command: Option<String>,
+}
+
 /// Returns the path to scrn's managed screenrc, creating it if needed.
 ///
 /// Sources the user's ~/.screenrc (if it exists), then enables truecolor
 /// so that 24-bit color sequences pass through GNU Screen — the same
 /// thing tmux/zellij do out of the box.
 pub fn ensure_screenrc() -> String {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let dir = PathBuf::from(&home).join(".config").join("scrn");
+    let dir = paths::runtime_dir();
     let _ = fs::create_dir_all(&dir);
     let path = dir.join("screenrc");
 
-    let user_rc = PathBuf::from(&home).join(".screenrc");
+    let user_rc = paths::home_dir().join(".screenrc");
     let mut content = String::new();
     if user_rc.exists() {
         content.push_str(&format!("source {}\n", user_rc.display()));
@@ -47,17 +60,31 @@ pub fn ensure_screenrc() -> String {
     content.push_str("defflow off\n");
     content.push_str("bindkey \"^S\" detach\n");
 
-    let _ = fs::write(&path, &content);
+    let _ = statefile::write_atomic(&path, &content);
     path.to_string_lossy().into_owned()
 }
 
-const MIN_SCREEN_MAJOR: u32 = 5;
+pub const MIN_SCREEN_MAJOR: u32 = 5;
+
+/// The version `<binary> --version` reports, e.g. "5.0.1" or "4.00.03".
+pub fn version(binary: &Path) -> std::io::Result<String> {
+    let output = Command::new(binary).arg("--version").logged_output()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    // Format: "Screen version 5.0.1 ..." or "Screen version 4.00.03 (FAU) ..."
+    Ok(text.split_whitespace().nth(2).unwrap_or("unknown").to_string())
+}
+
+pub fn version_major(version: &str) -> u32 {
+    version
+        .split('.')
+        .next()
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(0)
+}
 
 /// Check that GNU Screen is installed and >= 5.0 (required for truecolor).
 pub fn check_version() -> Result<(), String> {
-    let output = Command::new("screen")
-        .arg("--version")
-        .output()
+    let version_str = version(Path::new("screen"))
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 "GNU Screen is not installed.\n\n\
@@ -70,20 +97,7 @@ pub fn check_version() -> Result<(), String> {
             }
         })?;
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    // Format: "Screen version 5.0.1 ..." or "Screen version 4.00.03 (FAU) ..."
-    let version_str = text
-        .split_whitespace()
-        .nth(2)
-        .unwrap_or("unknown");
-
-    let major = version_str
-        .split('.')
-        .next()
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(0);
-
-    if major < MIN_SCREEN_MAJOR {
+    if version_major(&version_str) < MIN_SCREEN_MAJOR {
         Err(format!(
             "GNU Screen {version_str} is too old. scrn requires Screen 5.0+ for truecolor support.\n\n\
              Your screen: {version_str}\n\
@@ -97,10 +111,132 @@ pub fn check_version() -> Result<(), String> {
     }
 }
 
+/// Path of a session's socket (`~/.screen/<pid.name>`).
+pub fn socket_path(pid_name: &str) -> Option<PathBuf> {
+    Some(paths::socket_dir().join(pid_name))
+}
+
+/// Last-modified time of a session's socket. Screen touches it on output
+/// and attach/detach, so it doubles as a cheap "has anything changed" probe.
+pub fn socket_mtime(pid_name: &str) -> Option<SystemTime> {
+    fs::metadata(socket_path(pid_name)?).ok()?.modified().ok()
+}
+
+/// Why a screen command failed, worked out from what screen printed.
+#[derive(Debug)]
+pub enum Failure {
+    /// The session ended, or its socket went away, since it was listed
+    Gone,
+    /// The socket or its directory has the wrong owner or mode
+    Permission(String),
+    /// The session was started by an incompatible screen binary
+    Version(String),
+    Other(String),
+}
+
+impl Failure {
+    /// Classify screen's complaint. It prints most of them on stdout, so
+    /// `text` should hold both streams.
+    fn from_output(text: &str, status: ExitStatus) -> Self {
+        let lower = text.to_lowercase();
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("screen exited with {status}"));
+        if ["no screen session found", "there is no screen to be", "no screen to be resumed"]
+            .iter()
+            .any(|m| lower.contains(m))
+        {
+            Self::Gone
+        } else if ["permission denied", "must have mode", "not the owner", "must be owned", "bad permissions"]
+            .iter()
+            .any(|m| lower.contains(m))
+        {
+            Self::Permission(line)
+        } else if lower.contains("version")
+            && ["mismatch", "different", "protocol"].iter().any(|m| lower.contains(m))
+        {
+            Self::Version(line)
+        } else {
+            Self::Other(line)
+        }
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gone => f.write_str("the session is gone"),
+            Self::Permission(msg) => write!(
+                f,
+                "no access to the socket directory {} ({msg})",
+                paths::socket_dir().display()
+            ),
+            Self::Version(msg) => write!(f, "screen version mismatch, restart the session ({msg})"),
+            Self::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// `screen -S <session> -X <args>`: run a command in a running session.
+pub fn send(session: &str, args: &[&str]) -> Result<(), Failure> {
+    run(Command::new("screen").args(["-S", session, "-X"]).args(args))
+}
+
+/// `send`, aimed at one window instead of the session's current one.
+pub fn send_window(session: &str, window: &str, args: &[&str]) -> Result<(), Failure> {
+    run(Command::new("screen").args(["-S", session, "-p", window, "-X"]).args(args))
+}
+
+/// Run a screen command that doesn't need the terminal.
+pub fn run(cmd: &mut Command) -> Result<(), Failure> {
+    let output = cmd
+        .logged_output()
+        .map_err(|e| Failure::Other(format!("failed to run screen: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Err(Failure::from_output(&text, output.status))
+}
+
+/// Attach to `session` (a pid.name or a name) on this terminal, detaching
+/// it elsewhere first when `detach_others` is set. Screen owns the terminal
+/// meanwhile, so its output can't be captured; a failed attach is explained
+/// by checking whether the session still exists.
+pub fn attach(rc: &str, session: &str, detach_others: bool) -> Result<(), Failure> {
+    let mut cmd = Command::new("screen");
+    cmd.args(["-c", rc]);
+    if detach_others {
+        cmd.arg("-d");
+    }
+    let status = cmd
+        .args(["-r", session])
+        .logged_status()
+        .map_err(|e| Failure::Other(format!("failed to run screen: {e}")))?;
+    if status.success() {
+        return Ok(());
+    }
+    let exists = list_sessions()
+        .map(|all| all.iter().any(|s| s.pid_name == session || s.name == session))
+        .unwrap_or(true);
+    Err(if exists {
+        Failure::Other(format!("screen exited with {status}"))
+    } else {
+        Failure::Gone
+    })
+}
+
 pub fn list_sessions() -> Result<Vec<Session>, String> {
     let output = Command::new("screen")
         .arg("-ls")
-        .output()
+        .logged_output()
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 "GNU Screen is not installed. Install it with: brew install screen (macOS) or apt install screen (Linux)".to_string()
@@ -145,7 +281,8 @@ pub fn list_sessions() -> Result<Vec<Session>, String> {
 
         let rest = parts[1..].join("\t");
 
-        // Skip dead sessions (process exited but socket lingered)
+        // Skip dead sessions (process exited but socket lingered); `scrn wipe`
+        // lists and removes them
         if rest.contains("Dead") {
             continue;
         }
@@ -156,10 +293,8 @@ pub fn list_sessions() -> Result<Vec<Session>, String> {
             SessionState::Detached
         };
 
-        let (created, idle_secs) = std::env::var("HOME")
-            .ok()
-            .and_then(|home| {
-                let socket = PathBuf::from(&home).join(".screen").join(pid_name);
+        let (created, idle_secs) = socket_path(pid_name)
+            .and_then(|socket| {
                 fs::metadata(&socket).ok().map(|m| {
                     let created = m
                         .created()
@@ -188,6 +323,67 @@ pub fn list_sessions() -> Result<Vec<Session>, String> {
     Ok(sessions)
 }
 
+/// What `screen -ls` says beyond the live sessions.
+pub struct SocketReport {
+    /// The socket directory screen looked in, from its "Sockets in" line.
+    pub dir: Option<PathBuf>,
+    /// `pid.name` of sockets whose server is gone.
+    pub dead: Vec<String>,
+}
+
+pub fn socket_report() -> Result<SocketReport, String> {
+    let output = Command::new("screen")
+        .arg("-ls")
+        .logged_output()
+        .map_err(|e| format!("Failed to run screen: {e}"))?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    let mut report = SocketReport { dir: None, dead: Vec::new() };
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.contains('\t') {
+            let mut parts = trimmed.split('\t');
+            let pid_name = parts.next().unwrap_or("").trim();
+            if pid_name.contains('.') && parts.any(|p| p.contains("Dead")) {
+                report.dead.push(pid_name.to_string());
+            }
+        } else if trimmed.contains("Socket") {
+            // "2 Sockets in /run/screen/S-me." / "No Sockets found in ..."
+            if let Some((_, dir)) = trimmed.rsplit_once(" in ") {
+                report.dir = Some(PathBuf::from(dir.trim_end_matches('.')));
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Whether the PID in `pid.name` is still a screen server we may signal.
+/// PIDs get reused after a screen dies, so a live process only counts when
+/// its command name is screen; one owned by someone else (EPERM) never does.
+pub fn server_running(pid_name: &str) -> bool {
+    let Some(pid) = pid_name.split('.').next().and_then(|s| s.parse::<i32>().ok()) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists and is ours.
+    if unsafe { libc::kill(pid, 0) } != 0 {
+        return false;
+    }
+    let comm = match fs::read_to_string(format!("/proc/{pid}/comm")) {
+        Ok(comm) => comm,
+        // No procfs (macOS): ask ps.
+        Err(_) => match Command::new("ps").args(["-o", "comm=", "-p", &pid.to_string()]).logged_output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+            Err(_) => return false,
+        },
+    };
+    argv0_base(comm.trim()).eq_ignore_ascii_case("screen")
+}
+
+/// Whether the server answers `-Q windows` within the query timeout.
+pub fn responds(pid_name: &str) -> bool {
+    query(pid_name, None, &["windows"]).is_some()
+}
+
 pub fn kill_session(pid_name: &str) -> Result<(), String> {
     let pid = pid_name
         .split('.')
@@ -217,9 +413,8 @@ pub fn kill_session(pid_name: &str) -> Result<(), String> {
     }
 
     // screen -wipe doesn't reliably clean up on screen 5 — remove the socket directly
-    let _ = Command::new("screen").arg("-wipe").output();
-    if let Ok(home) = std::env::var("HOME") {
-        let socket = PathBuf::from(&home).join(".screen").join(pid_name);
+    let _ = Command::new("screen").arg("-wipe").logged_output();
+    if let Some(socket) = socket_path(pid_name) {
         let _ = fs::remove_file(&socket);
     }
 
@@ -231,7 +426,7 @@ pub fn create_session(name: &str) -> Result<(), String> {
     let output = Command::new("screen")
         .args(["-c", &rc, "-dmS", name])
         .env("COLORTERM", "truecolor")
-        .output()
+        .logged_output()
         .map_err(|e| format!("Failed to create session: {e}"))?;
 
     if output.status.success() {
@@ -242,6 +437,296 @@ pub fn create_session(name: &str) -> Result<(), String> {
     }
 }
 
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Run `screen -S <pid_name> [-p <window>] -Q <args>` and return its stdout.
+/// A session that stops answering would block `-Q` forever, so the query is
+/// killed after `QUERY_TIMEOUT`.
+pub fn query(pid_name: &str, window: Option<u32>, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new("screen");
+    cmd.args(["-S", pid_name]);
+    let win;
+    if let Some(n) = window {
+        win = n.to_string();
+        cmd.args(["-p", &win]);
+    }
+    cmd.arg("-Q").args(args);
+    let started = Instant::now();
+    let mut child = match cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            logging::log_command(&logging::describe(&cmd), started, &Err(e), &[], &[]);
+            return None;
+        }
+    };
+
+    let deadline = started + QUERY_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                logging::warn(&format!(
+                    "$ {}: no answer after {} ms, killed",
+                    logging::describe(&cmd),
+                    QUERY_TIMEOUT.as_millis()
+                ));
+                return None;
+            }
+        }
+    }
+    let output = child.wait_with_output().ok()?;
+    logging::log_command(
+        &logging::describe(&cmd),
+        started,
+        &Ok(output.status),
+        &output.stdout,
+        &output.stderr,
+    );
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Window numbers and flags of a session, from `-Q windows`.
+/// Format: `0$ bash  1*$ vim  2-$ cargo` — number, flag chars, title.
+pub fn list_windows(pid_name: &str) -> Vec<(u32, String)> {
+    let Some(text) = query(pid_name, None, &["windows"]) else {
+        return Vec::new();
+    };
+    let mut windows = Vec::new();
+    for entry in text.trim().split("  ") {
+        let entry = entry.trim();
+        let digits: String = entry.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let Ok(number) = digits.parse::<u32>() else { continue };
+        let flags: String = entry[digits.len()..]
+            .chars()
+            .take_while(|c| !c.is_whitespace())
+            .collect();
+        windows.push((number, flags));
+    }
+    windows
+}
+
+/// Title of window `number`, from `-p N -Q title`.
+pub fn window_title(pid_name: &str, number: u32) -> Option<String> {
+    query(pid_name, Some(number), &["title"])
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Current working directory of a process.
+pub fn process_cwd(pid: u32) -> Option<PathBuf> {
+    if let Ok(p) = fs::read_link(format!("/proc/{pid}/cwd")) {
+        return Some(p);
+    }
+    // macOS has no procfs — ask lsof for the cwd file descriptor.
+    let output = Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .stderr(Stdio::null())
+        .logged_output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|l| l.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+/// Dump a window's contents to `path` with `hardcopy` (`-h` includes the
+/// scrollback). Screen writes the file asynchronously, so wait briefly for
+/// it to appear. Returns `false` if nothing was written.
+pub fn hardcopy(pid_name: &str, window: u32, path: &Path, scrollback: bool) -> bool {
+    let _ = fs::remove_file(path);
+    let target = path.to_string_lossy();
+    let mut args = vec!["-S", pid_name, "-p"];
+    let win = window.to_string();
+    args.push(&win);
+    args.extend(["-X", "hardcopy"]);
+    if scrollback {
+        args.push("-h");
+    }
+    args.push(&target);
+    let ok = Command::new("screen")
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .logged_status()
+        .is_ok_and(|s| s.success());
+    if !ok {
+        return false;
+    }
+    for _ in 0..50 {
+        if fs::metadata(path).is_ok_and(|m| m.len() > 0) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    path.exists()
+}
+
+/// The `$WINDOW` screen gave a window's shell, where procfs exposes it.
+fn window_number(pid: u32) -> Option<u32> {
+    let environ = fs::read(format!("/proc/{pid}/environ")).ok()?;
+    environ
+        .split(|&b| b == 0)
+        .find_map(|var| var.strip_prefix(b"WINDOW="))
+        .and_then(|n| std::str::from_utf8(n).ok()?.parse().ok())
+}
+
+/// Capture every window of a session: number, title, cwd and foreground
+/// command. Window shells are matched to window numbers by their `$WINDOW`;
+/// shells whose environment can't be read (no procfs) fall back to spawn
+/// order (lowest child pid ↔ lowest remaining window number), which holds
+/// for windows that were never closed or renumbered.
+pub fn snapshot_windows(map: &ProcessMap, pid_name: &str) -> Vec<WindowInfo> {
+    let Some(screen_pid) = pid_name.split('.').next().and_then(|p| p.parse::<u32>().ok()) else {
+        return Vec::new();
+    };
+    let mut shells: Vec<u32> = map.children.get(&screen_pid).cloned().unwrap_or_default();
+    shells.sort_unstable();
+    let mut by_number: HashMap<u32, u32> = HashMap::new();
+    let mut unnumbered = Vec::new();
+    for pid in shells {
+        match window_number(pid) {
+            Some(number) => {
+                by_number.insert(number, pid);
+            }
+            None => unnumbered.push(pid),
+        }
+    }
+
+    let mut windows = list_windows(pid_name);
+    windows.sort_by_key(|(n, _)| *n);
+    let mut unnumbered = unnumbered.into_iter();
+
+    windows
+        .iter()
+        .map(|(number, _)| {
+            let shell = by_number.get(number).copied().or_else(|| unnumbered.next());
+            WindowInfo {
+                number: *number,
+                title: window_title(pid_name, *number).unwrap_or_default(),
+                cwd: shell.and_then(process_cwd),
+                command: shell.and_then(|pid| foreground_command(map, pid)),
+            }
+        })
+        .collect()
+}
+
+/// Snapshot the windows of every session in parallel, keyed by `pid_name`.
+pub fn snapshot_sessions(map: &ProcessMap, sessions: &[Session]) -> HashMap<String, Vec<WindowInfo>> {
+    std::thread::scope(|s| {
+        let handles: Vec<_> = sessions
+            .iter()
+            .map(|session| {
+                let pid_name = session.pid_name.as_str();
+                s.spawn(move || (pid_name.to_string(), snapshot_windows(map, pid_name)))
+            })
+            .collect();
+        handles.into_iter().filter_map(|h| h.join().ok()).collect()
+    })
+}
+
+/// Recreate a session window by window. The first window is started by
+/// `screen -dmS`; the rest are opened with `-X screen` after a `chdir`, so
+/// each lands in its own directory. Commands are typed in with `stuff` only
+/// when `run_commands` is set.
+pub fn restore_session(
+    name: &str,
+    dir: Option<&Path>,
+    windows: &[WindowInfo],
+    run_commands: bool,
+) -> Result<(), Failure> {
+    rebuild_session(name, dir, windows, &|_| Vec::new(), run_commands)
+}
+
+/// `restore_session`, with each window running the command line `program`
+/// gives for it instead of a shell when that isn't empty.
+pub fn rebuild_session(
+    name: &str,
+    dir: Option<&Path>,
+    windows: &[WindowInfo],
+    program: &dyn Fn(&WindowInfo) -> Vec<String>,
+    run_commands: bool,
+) -> Result<(), Failure> {
+    let rc = ensure_screenrc();
+    let mut cmd = Command::new("screen");
+    cmd.args(["-c", &rc, "-dmS", name]).env("COLORTERM", "truecolor");
+    let Some((first, rest)) = windows.split_first() else {
+        if let Some(d) = dir {
+            cmd.current_dir(d);
+        }
+        return run(&mut cmd);
+    };
+
+    if !first.title.is_empty() {
+        cmd.args(["-t", &first.title]);
+    }
+    cmd.args(program(first));
+    if let Some(d) = first.cwd.as_deref().filter(|d| d.is_dir()).or(dir) {
+        cmd.current_dir(d);
+    }
+    run(&mut cmd)?;
+
+    // The session always starts with window 0; give it the saved number so
+    // stuffing and the final select find it.
+    let first_number = first.number.to_string();
+    // The session exists by now, so say which step broke; the other kinds
+    // explain themselves.
+    let partly = |what: &str, e: Failure| match e {
+        Failure::Other(msg) => Failure::Other(format!("restored only partly, {what}: {msg}")),
+        e => e,
+    };
+    let step = |what: &str, args: &[&str]| send(name, args).map_err(|e| partly(what, e));
+    if first.number != 0 {
+        send_window(name, "0", &["number", &first_number]).map_err(|e| partly("renumbering window 0", e))?;
+    }
+
+    for win in rest {
+        let number = win.number.to_string();
+        if let Some(d) = win.cwd.as_deref().filter(|d| d.is_dir()).or(dir) {
+            step(&format!("window {number}"), &["chdir", &d.to_string_lossy()])?;
+        }
+        let program = program(win);
+        let mut args = vec!["screen"];
+        if !win.title.is_empty() {
+            args.extend(["-t", &win.title]);
+        }
+        args.push(&number);
+        args.extend(program.iter().map(String::as_str));
+        step(&format!("window {number}"), &args)?;
+    }
+    // Windows opened later start where a plain create would, not in the
+    // last restored window's directory.
+    match dir {
+        Some(d) => step("resetting the directory", &["chdir", &d.to_string_lossy()])?,
+        None => step("resetting the directory", &["chdir"])?,
+    }
+
+    if run_commands {
+        for win in windows {
+            if let Some(ref c) = win.command {
+                let number = win.number.to_string();
+                let stuff = format!("{c}\n");
+                send_window(name, &number, &["stuff", &stuff])
+                    .map_err(|e| partly(&format!("command in window {number}"), e))?;
+            }
+        }
+    }
+
+    step("selecting the first window", &["select", &first_number])
+}
+
 const SHELL_NAMES: &[&str] = &[
     "bash", "zsh", "sh", "fish", "dash", "ksh", "tcsh", "csh",
 ];
@@ -269,7 +754,7 @@ pub struct ProcessMap {
 }
 
 pub fn build_process_map() -> ProcessMap {
-    let output = match Command::new("ps").args(["-axo", "pid=,ppid=,args="]).output() {
+    let output = match Command::new("ps").args(["-axo", "pid=,ppid=,args="]).logged_output() {
         Ok(o) => o,
         Err(_) => return ProcessMap::default(),
     };
@@ -292,61 +777,95 @@ pub fn build_process_map() -> ProcessMap {
     ProcessMap { args_map, children }
 }
 
-/// For each screen session pid, return `true` if it has a non-shell
-/// foreground process somewhere in its child tree.
-pub fn has_foreground_from_map(map: &ProcessMap, session_pids: &[u32]) -> HashMap<u32, bool> {
-    if session_pids.is_empty() {
-        return HashMap::new();
+impl ProcessMap {
+    pub fn contains(&self, pid: u32) -> bool {
+        self.args_map.contains_key(&pid)
     }
-    let mut result: HashMap<u32, bool> = HashMap::new();
-    'outer: for &screen_pid in session_pids {
-        let mut frontier = vec![screen_pid];
-        let mut visited: std::collections::HashSet<u32> = std::collections::HashSet::new();
-        visited.insert(screen_pid);
-
-        for _ in 0..4 {
-            let mut next = Vec::new();
-            for pid in frontier {
-                let Some(kids) = map.children.get(&pid) else { continue };
-                for &kid in kids {
-                    if !visited.insert(kid) {
-                        continue;
-                    }
-                    let args = map.args_map.get(&kid).map(|s| s.as_str()).unwrap_or("");
-                    if is_shell_or_screen(args) {
-                        next.push(kid);
-                    } else {
-                        result.insert(screen_pid, true);
-                        continue 'outer;
-                    }
+}
+
+/// Args of the first non-shell process below `root` (breadth-first, a few
+/// levels deep), i.e. whatever is running in the foreground of a window.
+pub fn foreground_command(map: &ProcessMap, root: u32) -> Option<String> {
+    foreground_process(map, root).map(|(_, args)| args)
+}
+
+/// Like `foreground_command`, but also returns the process id.
+pub fn foreground_process(map: &ProcessMap, root: u32) -> Option<(u32, String)> {
+    let mut frontier = vec![root];
+    let mut visited: std::collections::HashSet<u32> = std::collections::HashSet::new();
+    visited.insert(root);
+
+    for _ in 0..4 {
+        let mut next = Vec::new();
+        for pid in frontier {
+            let Some(kids) = map.children.get(&pid) else { continue };
+            for &kid in kids {
+                if !visited.insert(kid) {
+                    continue;
+                }
+                let args = map.args_map.get(&kid).map(|s| s.as_str()).unwrap_or("");
+                if is_shell_or_screen(args) {
+                    next.push(kid);
+                } else {
+                    return Some((kid, args.to_string()));
                 }
-            }
-            frontier = next;
-            if frontier.is_empty() {
-                break;
             }
         }
-        result.insert(screen_pid, false);
+        frontier = next;
+        if frontier.is_empty() {
+            break;
+        }
     }
-    result
+    None
 }
 
+/// Open window `number` in a running session and type `command` into its shell.
+pub fn add_window(session: &str, number: u32, title: Option<&str>, command: Option<&str>) -> Result<(), Failure> {
+    let mut args = vec!["screen"];
+    if let Some(t) = title {
+        args.extend(["-t", t]);
+    }
+    let n = number.to_string();
+    args.push(&n);
+    send(session, &args)?;
+    if let Some(c) = command {
+        let stuff = format!("{c}\n");
+        send_window(session, &n, &["stuff", &stuff])?;
+    }
+    Ok(())
+}
 
-pub fn create_session_in_dir(name: &str, dir: &std::path::Path) -> Result<(), String> {
-    let rc = ensure_screenrc();
-    let output = Command::new("screen")
-        .args(["-c", &rc, "-dmS", name])
-        .current_dir(dir)
-        .env("COLORTERM", "truecolor")
-        .output()
-        .map_err(|e| format!("Failed to create session: {e}"))?;
+/// Seconds a process has been running, from `ps -o etime=`
+/// (`[[dd-]hh:]mm:ss` — `etimes` isn't available on macOS).
+pub fn process_elapsed(pid: u32) -> Option<u64> {
+    let output = Command::new("ps")
+        .args(["-o", "etime=", "-p", &pid.to_string()])
+        .logged_output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let text = text.trim();
+    let (days, clock) = match text.split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, rest),
+        None => (0, text),
+    };
+    let mut secs = 0u64;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86400 + secs)
+}
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to create session: {}", stderr.trim()))
+/// For each screen session pid, return `true` if it has a non-shell
+/// foreground process somewhere in its child tree.
+pub fn has_foreground_from_map(map: &ProcessMap, session_pids: &[u32]) -> HashMap<u32, bool> {
+    if session_pids.is_empty() {
+        return HashMap::new();
     }
+    session_pids
+        .iter()
+        .map(|&pid| (pid, foreground_command(map, pid).is_some()))
+        .collect()
 }
 
 
+
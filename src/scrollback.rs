@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::SystemTime;
+
+use crate::paths;
+use crate::screen::{self, Session};
+
+/// Most matching lines reported per session, so one chatty log can't bury
+/// the rest of the results.
+const MAX_MATCHES_PER_SESSION: usize = 20;
+
+/// A line of scrollback that contains the query.
+#[derive(Clone, Debug)]
+pub struct ContentMatch {
+    pub name: String,
+    pub pid_name: String,
+    pub window: u32,
+    pub line: String,
+    /// char range of the match within `line`
+    pub start: usize,
+    pub end: usize,
+}
+
+pub struct CachedScrollback {
+    mtime: Option<SystemTime>,
+    windows: Vec<(u32, Vec<String>)>,
+}
+
+/// Captured scrollback per session, reused until the session's socket
+/// mtime changes.
+#[derive(Default)]
+pub struct ScrollbackCache {
+    entries: HashMap<String, CachedScrollback>,
+}
+
+impl ScrollbackCache {
+    /// Sessions whose cache entry is stale, with their socket mtime. Entries
+    /// for sessions that no longer exist are dropped.
+    pub fn stale(&mut self, sessions: &[Session]) -> Vec<(String, Option<SystemTime>)> {
+        self.entries
+            .retain(|pid_name, _| sessions.iter().any(|s| &s.pid_name == pid_name));
+
+        sessions
+            .iter()
+            .filter(|s| !s.pid_name.is_empty())
+            .map(|s| (s.pid_name.clone(), screen::socket_mtime(&s.pid_name)))
+            .filter(|(pid_name, mtime)| {
+                self.entries
+                    .get(pid_name)
+                    .is_none_or(|c| c.mtime.is_none() || c.mtime != *mtime)
+            })
+            .collect()
+    }
+
+    /// Store what `capture_all` returned.
+    pub fn insert(&mut self, captured: Vec<(String, CachedScrollback)>) {
+        self.entries.extend(captured);
+    }
+
+    /// Case-insensitive substring search over every cached session.
+    pub fn search(&self, sessions: &[Session], query: &str) -> Vec<ContentMatch> {
+        let needle: Vec<char> = query.chars().flat_map(|c| c.to_lowercase()).collect();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut results = Vec::new();
+        for session in sessions {
+            let Some(cached) = self.entries.get(&session.pid_name) else { continue };
+            let mut found = 0;
+            // Newest output last in scrollback — report the most recent hits first
+            'windows: for (window, lines) in &cached.windows {
+                for line in lines.iter().rev() {
+                    let Some((start, end)) = find_ci(line, &needle) else { continue };
+                    results.push(ContentMatch {
+                        name: session.name.clone(),
+                        pid_name: session.pid_name.clone(),
+                        window: *window,
+                        line: line.trim_end().to_string(),
+                        start,
+                        end,
+                    });
+                    found += 1;
+                    if found >= MAX_MATCHES_PER_SESSION {
+                        break 'windows;
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+/// Capture the scrollback of the `stale` sessions, in parallel. Each window
+/// takes a hardcopy, so this belongs on a background thread.
+pub fn capture_all(stale: Vec<(String, Option<SystemTime>)>) -> Vec<(String, CachedScrollback)> {
+    std::thread::scope(|s| {
+        let handles: Vec<_> = stale
+            .into_iter()
+            .map(|(pid_name, mtime)| {
+                s.spawn(move || {
+                    let windows = capture(&pid_name);
+                    (pid_name, CachedScrollback { mtime, windows })
+                })
+            })
+            .collect();
+        handles.into_iter().filter_map(|h| h.join().ok()).collect()
+    })
+}
+
+/// Hardcopy every window's scrollback into a temp file and read it back.
+fn capture(pid_name: &str) -> Vec<(u32, Vec<String>)> {
+    let tmp = paths::scratch_dir();
+    screen::list_windows(pid_name)
+        .into_iter()
+        .filter_map(|(number, _)| {
+            let path = tmp.join(format!("scrn-{}-{pid_name}-{number}.txt", std::process::id()));
+            let ok = screen::hardcopy(pid_name, number, &path, true);
+            let text = fs::read(&path)
+                .map(|b| String::from_utf8_lossy(&b).into_owned())
+                .ok();
+            let _ = fs::remove_file(&path);
+            if !ok {
+                return None;
+            }
+            let lines = text?.lines().map(|l| l.to_string()).collect();
+            Some((number, lines))
+        })
+        .collect()
+}
+
+/// Char range of the first case-insensitive occurrence of `needle`, which
+/// must already be lowercased. The haystack is lowercased the same way,
+/// and since a char can lowercase to several (`İ` to `i̇`), each lowered
+/// char remembers which original char it came from.
+fn find_ci(haystack: &str, needle: &[char]) -> Option<(usize, usize)> {
+    let mut hay = Vec::new();
+    let mut origin = Vec::new();
+    for (i, c) in haystack.chars().enumerate() {
+        for lc in c.to_lowercase() {
+            hay.push(lc);
+            origin.push(i);
+        }
+    }
+    if needle.is_empty() || needle.len() > hay.len() {
+        return None;
+    }
+    let at = (0..=hay.len() - needle.len()).find(|&start| hay[start..start + needle.len()] == *needle)?;
+    Some((origin[at], origin[at + needle.len() - 1] + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lower(s: &str) -> Vec<char> {
+        s.chars().flat_map(char::to_lowercase).collect()
+    }
+
+    #[test]
+    fn find_ci_ignores_case() {
+        assert_eq!(find_ci("cargo BUILD failed", &lower("build")), Some((6, 11)));
+        assert_eq!(find_ci("nothing here", &lower("build")), None);
+    }
+
+    #[test]
+    fn find_ci_matches_chars_that_lowercase_to_several() {
+        // `İ` lowercases to `i` plus a combining dot
+        assert_eq!(find_ci("İstanbul", &lower("İSTANBUL")), Some((0, 8)));
+        assert_eq!(find_ci("xİy", &lower("İ")), Some((1, 2)));
+    }
+}
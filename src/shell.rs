@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::paths;
+
 pub fn init_script(shell: &str) -> Result<String, String> {
     match shell {
         "zsh" => Ok(zsh_script()),
@@ -47,3 +51,39 @@ scrn() {
 "#
     .to_string()
 }
+
+/// The rc files `eval "$(scrn init <shell>)"` belongs in, most likely first.
+pub fn rc_files(shell: &str) -> Vec<PathBuf> {
+    let home = paths::home_dir();
+    match shell {
+        "zsh" => {
+            let dir = std::env::var_os("ZDOTDIR")
+                .filter(|d| !d.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or(home);
+            vec![dir.join(".zshrc")]
+        }
+        "bash" => vec![home.join(".bashrc"), home.join(".bash_profile")],
+        _ => Vec::new(),
+    }
+}
+
+/// Disable terminal flow control (Ctrl+S / Ctrl+Q) so Ctrl+S reaches screen.
+pub fn disable_flow_control() {
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(0, &mut termios) == 0 {
+            termios.c_iflag &= !libc::IXON;
+            libc::tcsetattr(0, libc::TCSANOW, &termios);
+        }
+    }
+}
+
+/// Whether stdin's terminal has flow control on, or `None` when stdin
+/// isn't a terminal.
+pub fn flow_control_on() -> Option<bool> {
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        (libc::tcgetattr(0, &mut termios) == 0).then_some(termios.c_iflag & libc::IXON != 0)
+    }
+}
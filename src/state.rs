@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::WorkspaceRoot;
+use crate::logging;
+use crate::paths;
+use crate::screen::WindowInfo;
+use crate::statefile;
+
+/// Schema version written in the header. Bump it when a record changes
+/// meaning; older files are upgraded in `parse`, newer ones are left alone.
+const VERSION: u32 = 1;
+
+/// A session recorded in the last snapshot, to be rebuilt on startup.
+#[derive(Clone)]
+pub struct SavedSession {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub windows: Vec<WindowInfo>,
+}
+
+/// Everything scrn remembers between runs.
+#[derive(Default)]
+pub struct State {
+    pub pins: HashSet<String>,
+    /// In display order.
+    pub constants: Vec<String>,
+    pub constant_commands: HashMap<String, String>,
+    pub tags: HashMap<String, Vec<String>>,
+    /// Session name to the unix time it was last opened.
+    pub history: HashMap<String, u64>,
+    pub sessions: Vec<SavedSession>,
+    /// Workspace root name to its top-level directories in display order.
+    pub dir_order: HashMap<String, Vec<String>>,
+    pub folded_dirs: HashSet<String>,
+    /// `.scrn.toml` path to the hash of the contents the user trusted to
+    /// run commands and set env.
+    pub trusted: HashMap<String, u64>,
+    /// Set when the file was written by a newer scrn or can't be read;
+    /// such a file is never overwritten.
+    read_only: bool,
+}
+
+fn state_path() -> PathBuf {
+    paths::state_dir().join("state")
+}
+
+pub fn load() -> State {
+    match fs::read_to_string(state_path()) {
+        Ok(contents) => parse(&contents),
+        Err(_) => State::default(),
+    }
+}
+
+/// Re-read the state under its lock, apply `change` and write it back.
+/// Returns the merged state, which includes whatever another scrn instance
+/// saved since this one loaded.
+pub fn update(change: impl FnOnce(&mut State)) -> State {
+    let path = state_path();
+    let lock = statefile::lock(&path);
+    if let Err(ref e) = lock {
+        logging::error(&format!("Failed to lock {}: {e}", path.display()));
+    }
+    let old = fs::read_to_string(&path).unwrap_or_default();
+    let mut state = parse(&old);
+    change(&mut state);
+    if state.read_only {
+        logging::warn(&format!(
+            "{} is from a newer scrn or damaged; not saving",
+            path.display()
+        ));
+        return state;
+    }
+    let new = render(&state);
+    if new != old {
+        if let Err(e) = statefile::write_atomic(&path, &new) {
+            logging::error(&format!("Failed to write {}: {e}", path.display()));
+        }
+    }
+    state
+}
+
+/// Fields are tab-separated, so tabs, newlines and backslashes inside a
+/// field are backslash-escaped. The event log uses the same scheme.
+pub fn escape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn unescape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Format: a `scrn-state <version>` header, then one record per line, a kind
+/// followed by its fields:
+///
+/// ```text
+/// pin       <name>
+/// constant  <name>
+/// command   <name> <command>
+/// tag       <name> <tag>...
+/// opened    <name> <unix time>
+/// dir       <root> <dir>
+/// folded    <path>
+/// trusted   <path> <hash>
+/// session   <name> <path or empty>
+/// window    <number> <title> <cwd> <command>
+/// ```
+///
+/// `window` records belong to the `session` before them. Unknown kinds are
+/// skipped.
+fn render(state: &State) -> String {
+    fn record(out: &mut String, fields: &[&str]) {
+        let fields: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+        let mut entries: Vec<(&K, &V)> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    let mut out = format!("scrn-state {VERSION}\n");
+    let mut pins: Vec<&String> = state.pins.iter().collect();
+    pins.sort();
+    for name in pins {
+        record(&mut out, &["pin", name]);
+    }
+    for name in &state.constants {
+        record(&mut out, &["constant", name]);
+    }
+    for (name, command) in sorted(&state.constant_commands) {
+        record(&mut out, &["command", name, command]);
+    }
+    for (name, tags) in sorted(&state.tags) {
+        let mut fields = vec!["tag", name.as_str()];
+        fields.extend(tags.iter().map(String::as_str));
+        record(&mut out, &fields);
+    }
+    for (name, ts) in sorted(&state.history) {
+        record(&mut out, &["opened", name, &ts.to_string()]);
+    }
+    for (root, dirs) in sorted(&state.dir_order) {
+        for dir in dirs {
+            record(&mut out, &["dir", root, dir]);
+        }
+    }
+    let mut folded: Vec<&String> = state.folded_dirs.iter().collect();
+    folded.sort();
+    for path in folded {
+        record(&mut out, &["folded", path]);
+    }
+    for (path, hash) in sorted(&state.trusted) {
+        record(&mut out, &["trusted", path, &format!("{hash:016x}")]);
+    }
+    for session in &state.sessions {
+        let path = session.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+        record(&mut out, &["session", &session.name, &path]);
+        for w in &session.windows {
+            let cwd = w.cwd.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+            record(&mut out, &[
+                "window",
+                &w.number.to_string(),
+                &w.title,
+                &cwd,
+                w.command.as_deref().unwrap_or(""),
+            ]);
+        }
+    }
+    out
+}
+
+fn parse(contents: &str) -> State {
+    let mut state = State::default();
+    let mut lines = contents.lines();
+    let version = lines
+        .next()
+        .and_then(|l| l.strip_prefix("scrn-state "))
+        .and_then(|v| v.trim().parse::<u32>().ok());
+    match version {
+        Some(v) if v <= VERSION => {}
+        _ if contents.is_empty() => {}
+        _ => state.read_only = true,
+    }
+    for line in lines.filter(|l| !l.is_empty()) {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        let non_empty = |i: usize| fields.get(i).filter(|f| !f.is_empty()).cloned();
+        match (fields[0].as_str(), non_empty(1)) {
+            ("pin", Some(name)) => {
+                state.pins.insert(name);
+            }
+            ("constant", Some(name)) if !state.constants.contains(&name) => {
+                state.constants.push(name);
+            }
+            ("command", Some(name)) => {
+                if let Some(command) = non_empty(2) {
+                    state.constant_commands.insert(name, command);
+                }
+            }
+            ("tag", Some(name)) => {
+                let tags: Vec<String> = fields[2..].iter().filter(|t| !t.is_empty()).cloned().collect();
+                if !tags.is_empty() {
+                    state.tags.insert(name, tags);
+                }
+            }
+            ("opened", Some(name)) => {
+                if let Some(ts) = non_empty(2).and_then(|t| t.parse().ok()) {
+                    state.history.insert(name, ts);
+                }
+            }
+            ("dir", Some(root)) => {
+                if let Some(dir) = non_empty(2) {
+                    state.dir_order.entry(root).or_default().push(dir);
+                }
+            }
+            ("folded", Some(path)) => {
+                state.folded_dirs.insert(path);
+            }
+            ("trusted", Some(path)) => {
+                if let Some(hash) = non_empty(2).and_then(|h| u64::from_str_radix(&h, 16).ok()) {
+                    state.trusted.insert(path, hash);
+                }
+            }
+            ("session", Some(name)) => {
+                state.sessions.push(SavedSession {
+                    name,
+                    path: non_empty(2).map(PathBuf::from),
+                    windows: Vec::new(),
+                });
+            }
+            ("window", Some(number)) => {
+                let (Ok(number), Some(session)) = (number.parse(), state.sessions.last_mut()) else {
+                    continue;
+                };
+                session.windows.push(WindowInfo {
+                    number,
+                    title: fields.get(2).cloned().unwrap_or_default(),
+                    cwd: non_empty(3).map(PathBuf::from),
+                    command: non_empty(4),
+                });
+            }
+            _ => {}
+        }
+    }
+    state
+}
+
+/// Files from before the state store, one per kind, in the legacy directory.
+const LEGACY_FILES: [&str; 7] = [
+    "pins",
+    "constants",
+    "constant_commands",
+    "history",
+    "sessions",
+    "dir_order",
+    "folded_dirs",
+];
+
+/// Move what older versions kept in `~/.config/scrn` to where it lives now.
+/// `roots` places `dir_order`, which predates multiple roots.
+pub fn migrate(roots: &[WorkspaceRoot]) {
+    migrate_store(roots);
+    let dir = paths::legacy_dir();
+    move_legacy(&dir.join("events"), &paths::events_file());
+    move_legacy(&dir.join("scrn.log"), &paths::log_file());
+    // Generated on every start; it now lives in the runtime directory
+    let _ = fs::remove_file(dir.join("screenrc"));
+}
+
+/// Move `from` to `to` unless `to` already exists.
+fn move_legacy(from: &Path, to: &Path) {
+    if !from.exists() || to.exists() || from == to {
+        return;
+    }
+    if let Some(parent) = to.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // Copy when the two are on different filesystems
+    let moved = fs::rename(from, to).is_ok()
+        || (fs::copy(from, to).is_ok() && fs::remove_file(from).is_ok());
+    if !moved {
+        logging::warn(&format!("Failed to move {} to {}", from.display(), to.display()));
+    }
+}
+
+/// Fold the legacy files into the state store once: when there is no state
+/// file yet and at least one legacy file. The legacy files are removed after
+/// the store is written.
+fn migrate_store(roots: &[WorkspaceRoot]) {
+    let path = state_path();
+    let dir = paths::legacy_dir();
+    if path.exists() || !LEGACY_FILES.iter().any(|f| dir.join(f).exists()) {
+        return;
+    }
+    let lock = statefile::lock(&path);
+    if path.exists() {
+        // Another instance migrated while we waited for the lock
+        return;
+    }
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap_or_default();
+    let lines = |contents: String| -> Vec<String> {
+        contents.lines().filter(|l| !l.is_empty()).map(str::to_string).collect()
+    };
+    let pairs = |contents: String, sep: char| -> Vec<(String, String)> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|l| l.split_once(sep))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+            .collect()
+    };
+
+    let mut state = State {
+        pins: lines(read("pins")).into_iter().collect(),
+        constant_commands: pairs(read("constant_commands"), '=').into_iter().collect(),
+        folded_dirs: lines(read("folded_dirs")).into_iter().collect(),
+        ..State::default()
+    };
+    for name in lines(read("constants")) {
+        if !state.constants.contains(&name) {
+            state.constants.push(name);
+        }
+    }
+    for (name, ts) in pairs(read("history"), '\t') {
+        if let Ok(ts) = ts.parse() {
+            state.history.insert(name, ts);
+        }
+    }
+    // Directory names, from when there was a single root
+    let dirs = lines(read("dir_order"));
+    if !dirs.is_empty() {
+        let root = roots.first().map(|r| r.name.clone()).unwrap_or_default();
+        state.dir_order.insert(root, dirs);
+    }
+    // `name[\tpath]` per session
+    for line in lines(read("sessions")) {
+        let (name, path) = match line.split_once('\t') {
+            Some((name, path)) => (name.to_string(), Some(PathBuf::from(path))),
+            None => (line, None),
+        };
+        state.sessions.push(SavedSession { name, path, windows: Vec::new() });
+    }
+
+    if let Err(e) = statefile::write_atomic(&path, &render(&state)) {
+        logging::error(&format!("Failed to write {}: {e}", path.display()));
+        return;
+    }
+    for name in LEGACY_FILES {
+        let _ = fs::remove_file(dir.join(name));
+        let _ = fs::remove_file(dir.join(format!("{name}.lock")));
+    }
+    drop(lock);
+    logging::info(&format!(
+        "Migrated state from {} to {}",
+        dir.display(),
+        path.display()
+    ));
+}
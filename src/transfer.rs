@@ -0,0 +1,489 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{parse_list, parse_value, WorkspaceRoot};
+use crate::logging::format_utc;
+use crate::state::{self, SavedSession, State};
+
+/// Bumped when the export format changes meaning.
+const VERSION: u32 = 1;
+
+/// What `scrn export` carries: the parts of the state that make sense on
+/// another machine. History and fold state stay behind.
+#[derive(Default)]
+struct Export {
+    pins: Vec<String>,
+    constants: Vec<String>,
+    commands: Vec<(String, String)>,
+    tags: Vec<(String, Vec<String>)>,
+    dir_order: Vec<(String, Vec<String>)>,
+    /// Session name and its path, workspace-relative (`<root>/<dir>`) when
+    /// it lies under a root.
+    sessions: Vec<(String, Option<String>)>,
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn quote_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|i| quote(i)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// `<root>/<dir>` for a path under a workspace root, else the path as is.
+fn relative_to_roots(path: &Path, roots: &[WorkspaceRoot]) -> String {
+    for root in roots {
+        if let Ok(rel) = path.strip_prefix(&root.path) {
+            let rel = rel.to_string_lossy();
+            return if rel.is_empty() {
+                root.name.clone()
+            } else {
+                format!("{}/{rel}", root.name)
+            };
+        }
+    }
+    path.display().to_string()
+}
+
+/// The root an exported root name stands for here: the one with that name,
+/// or the only one when there is just one.
+fn local_root<'a>(name: &str, roots: &'a [WorkspaceRoot]) -> Option<&'a WorkspaceRoot> {
+    roots
+        .iter()
+        .find(|r| r.name == name)
+        .or(if roots.len() == 1 { roots.first() } else { None })
+}
+
+/// Inverse of `relative_to_roots`.
+fn resolve(path: &str, roots: &[WorkspaceRoot]) -> Result<PathBuf, String> {
+    if path.starts_with('/') {
+        return Ok(PathBuf::from(path));
+    }
+    let (name, rel) = path.split_once('/').unwrap_or((path, ""));
+    let root = local_root(name, roots).ok_or_else(|| format!("no workspace root '{name}'"))?;
+    Ok(if rel.is_empty() { root.path.clone() } else { root.path.join(rel) })
+}
+
+/// `scrn export`: the state as TOML on stdout.
+pub fn export(roots: &[WorkspaceRoot]) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    render(&state::load(), roots, now)
+}
+
+fn render(st: &State, roots: &[WorkspaceRoot], now: u64) -> String {
+    let sorted = |set: &HashSet<String>| {
+        let mut v: Vec<String> = set.iter().cloned().collect();
+        v.sort();
+        v
+    };
+    let mut out = format!("# scrn export, {} UTC\nversion = {VERSION}\n", format_utc(now));
+    out.push_str(&format!("pins = {}\n", quote_list(&sorted(&st.pins))));
+    out.push_str(&format!("constants = {}\n", quote_list(&st.constants)));
+
+    let mut commands: Vec<(&String, &String)> = st.constant_commands.iter().collect();
+    commands.sort();
+    if !commands.is_empty() {
+        out.push_str("\n[commands]\n");
+        for (name, command) in commands {
+            out.push_str(&format!("{} = {}\n", quote(name), quote(command)));
+        }
+    }
+    let mut tags: Vec<(&String, &Vec<String>)> = st.tags.iter().collect();
+    tags.sort();
+    if !tags.is_empty() {
+        out.push_str("\n[tags]\n");
+        for (name, tags) in tags {
+            out.push_str(&format!("{} = {}\n", quote(name), quote_list(tags)));
+        }
+    }
+    let mut order: Vec<(&String, &Vec<String>)> = st.dir_order.iter().collect();
+    order.sort();
+    if !order.is_empty() {
+        out.push_str("\n[dir_order]\n");
+        for (root, dirs) in order {
+            out.push_str(&format!("{} = {}\n", quote(root), quote_list(dirs)));
+        }
+    }
+    for session in &st.sessions {
+        out.push_str(&format!("\n[[sessions]]\nname = {}\n", quote(&session.name)));
+        if let Some(ref path) = session.path {
+            out.push_str(&format!("path = {}\n", quote(&relative_to_roots(path, roots))));
+        }
+    }
+    out
+}
+
+/// `"quoted key" = value` or `key = value`.
+fn split_key(line: &str) -> Option<(String, &str)> {
+    if line.starts_with('"') {
+        let mut escaped = false;
+        for (i, c) in line.char_indices().skip(1) {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                let key = parse_value(&line[..=i]);
+                let value = line[i + 1..].trim_start().strip_prefix('=')?;
+                return Some((key, value));
+            }
+        }
+        return None;
+    }
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim().to_string(), value))
+}
+
+fn parse(contents: &str) -> Result<Export, String> {
+    let mut ex = Export::default();
+    let mut section = String::new();
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line == "[[sessions]]" {
+            ex.sessions.push((String::new(), None));
+            section = "sessions".to_string();
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = header.trim().to_string();
+            continue;
+        }
+        let (key, value) = split_key(line).ok_or_else(|| format!("line {}: expected key = value", n + 1))?;
+        match (section.as_str(), key.as_str()) {
+            ("", "version") => {
+                let v: u32 = value.trim().parse().map_err(|_| format!("line {}: bad version", n + 1))?;
+                if v > VERSION {
+                    return Err(format!("written by a newer scrn (format {v})"));
+                }
+            }
+            ("", "pins") => ex.pins = parse_list(value),
+            ("", "constants") => ex.constants = parse_list(value),
+            ("commands", _) => ex.commands.push((key, parse_value(value))),
+            ("tags", _) => ex.tags.push((key, parse_list(value))),
+            ("dir_order", _) => ex.dir_order.push((key, parse_list(value))),
+            ("sessions", "name") => {
+                if let Some(s) = ex.sessions.last_mut() {
+                    s.0 = parse_value(value);
+                }
+            }
+            ("sessions", "path") => {
+                if let Some(s) = ex.sessions.last_mut() {
+                    s.1 = Some(parse_value(value)).filter(|p| !p.is_empty());
+                }
+            }
+            _ => {}
+        }
+    }
+    ex.sessions.retain(|(name, _)| !name.is_empty());
+    Ok(ex)
+}
+
+/// `scrn import <file> [--merge]`. Without `--merge` the imported sections
+/// replace the local ones; with it they are added to them and the local
+/// value wins where both have one. Either way every disagreement is listed.
+pub fn import(args: &[String], roots: &[WorkspaceRoot]) -> Result<(), String> {
+    let mut merge = false;
+    let mut file = None;
+    for arg in args {
+        match arg.as_str() {
+            "--merge" => merge = true,
+            other if other.starts_with('-') && other != "-" => {
+                return Err(format!("unknown argument '{other}'"));
+            }
+            other => file = Some(other.to_string()),
+        }
+    }
+    let file = file.ok_or("usage: scrn import <file> [--merge]")?;
+    let contents = if file == "-" {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| format!("stdin: {e}"))?
+    } else {
+        fs::read_to_string(&file).map_err(|e| format!("{file}: {e}"))?
+    };
+    let ex = parse(&contents).map_err(|e| format!("{file}: {e}"))?;
+
+    let mut conflicts: Vec<String> = Vec::new();
+    let (sessions, dir_order) = localize(&ex, roots, &mut conflicts);
+    state::update(|st| apply(st, &ex, &sessions, &dir_order, merge, &mut conflicts));
+
+    println!(
+        "Imported {} pins, {} constants, {} commands, {} tagged names, {} dir orders, {} sessions{}",
+        ex.pins.len(),
+        ex.constants.len(),
+        ex.commands.len(),
+        ex.tags.len(),
+        dir_order.len(),
+        sessions.len(),
+        if merge { " (merged)" } else { "" }
+    );
+    if !conflicts.is_empty() {
+        println!("\n{} conflicts:", conflicts.len());
+        for c in &conflicts {
+            println!("  {c}");
+        }
+    }
+    Ok(())
+}
+
+/// The imported sessions and dir orders with their roots resolved to local
+/// ones. Those naming a root that isn't here are skipped and listed.
+fn localize(
+    ex: &Export,
+    roots: &[WorkspaceRoot],
+    conflicts: &mut Vec<String>,
+) -> (Vec<SavedSession>, Vec<(String, Vec<String>)>) {
+    let mut sessions: Vec<SavedSession> = Vec::new();
+    for (name, path) in &ex.sessions {
+        let path = match path.as_deref().map(|p| resolve(p, roots)) {
+            Some(Ok(p)) => Some(p),
+            Some(Err(e)) => {
+                conflicts.push(format!("session '{name}': {e}, skipped"));
+                continue;
+            }
+            None => None,
+        };
+        sessions.push(SavedSession { name: name.clone(), path, windows: Vec::new() });
+    }
+    let mut dir_order: Vec<(String, Vec<String>)> = Vec::new();
+    for (root, dirs) in &ex.dir_order {
+        match local_root(root, roots) {
+            Some(r) => dir_order.push((r.name.clone(), dirs.clone())),
+            None => conflicts.push(format!("dir order for '{root}': no such workspace root, skipped")),
+        }
+    }
+    (sessions, dir_order)
+}
+
+/// Replace or merge the imported sections into `st`, listing every
+/// disagreement with what was there.
+fn apply(
+    st: &mut State,
+    ex: &Export,
+    sessions: &[SavedSession],
+    dir_order: &[(String, Vec<String>)],
+    merge: bool,
+    conflicts: &mut Vec<String>,
+) {
+    let winner = if merge { "kept local" } else { "using imported" };
+    let old_pins = std::mem::take(&mut st.pins);
+    let old_constants = std::mem::take(&mut st.constants);
+    let old_commands = std::mem::take(&mut st.constant_commands);
+    let old_tags = std::mem::take(&mut st.tags);
+    let old_order = std::mem::take(&mut st.dir_order);
+    let old_sessions = std::mem::take(&mut st.sessions);
+
+    // A name is either pinned or a constant, never both. Without
+    // --merge the local lists are dropped, so they can't disagree.
+    if merge {
+        for name in ex.pins.iter().filter(|n| old_constants.contains(n)) {
+            conflicts.push(format!("'{name}' is a constant here but pinned in the import; {winner}"));
+        }
+        for name in ex.constants.iter().filter(|n| old_pins.contains(*n)) {
+            conflicts.push(format!("'{name}' is pinned here but a constant in the import; {winner}"));
+        }
+    }
+    for (name, command) in &ex.commands {
+        if let Some(local) = old_commands.get(name).filter(|c| *c != command) {
+            conflicts.push(format!("command for '{name}': '{local}' here, '{command}' imported; {winner}"));
+        }
+    }
+    for (name, tags) in &ex.tags {
+        if let Some(local) = old_tags.get(name).filter(|t| *t != tags) {
+            conflicts.push(format!(
+                "tags for '{name}': {} here, {} imported; {winner}",
+                local.join(","),
+                tags.join(",")
+            ));
+        }
+    }
+    for (root, dirs) in dir_order {
+        if old_order.get(root).is_some_and(|local| local != dirs) {
+            conflicts.push(format!("dir order for '{root}' differs; {winner}"));
+        }
+    }
+    for s in sessions {
+        if let Some(local) = old_sessions.iter().find(|l| l.name == s.name && l.path != s.path) {
+            let show = |p: &Option<PathBuf>| p.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+            conflicts.push(format!(
+                "session '{}': {} here, {} imported; {winner}",
+                s.name,
+                show(&local.path),
+                show(&s.path)
+            ));
+        }
+    }
+
+    if merge {
+        st.pins = old_pins;
+        st.constants = old_constants;
+        for name in &ex.pins {
+            if !st.constants.contains(name) {
+                st.pins.insert(name.clone());
+            }
+        }
+        for name in &ex.constants {
+            if !st.pins.contains(name) && !st.constants.contains(name) {
+                st.constants.push(name.clone());
+            }
+        }
+        st.constant_commands = old_commands;
+        for (name, command) in &ex.commands {
+            st.constant_commands.entry(name.clone()).or_insert_with(|| command.clone());
+        }
+        st.tags = old_tags;
+        for (name, tags) in &ex.tags {
+            st.tags.entry(name.clone()).or_insert_with(|| tags.clone());
+        }
+        st.dir_order = old_order;
+        for (root, dirs) in dir_order {
+            st.dir_order.entry(root.clone()).or_insert_with(|| dirs.clone());
+        }
+        st.sessions = old_sessions;
+        for s in sessions {
+            if !st.sessions.iter().any(|l| l.name == s.name) {
+                st.sessions.push(s.clone());
+            }
+        }
+    } else {
+        st.constants = ex.constants.clone();
+        st.pins = ex.pins.iter().filter(|n| !st.constants.contains(n)).cloned().collect();
+        st.constant_commands = ex.commands.iter().cloned().collect();
+        st.tags = ex.tags.iter().cloned().collect::<HashMap<_, _>>();
+        st.dir_order = dir_order.iter().cloned().collect();
+        st.sessions = sessions.to_vec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(name: &str, path: &str) -> WorkspaceRoot {
+        WorkspaceRoot { name: name.into(), path: path.into() }
+    }
+
+    fn session(name: &str, path: Option<&str>) -> SavedSession {
+        SavedSession { name: name.into(), path: path.map(PathBuf::from), windows: Vec::new() }
+    }
+
+    /// Import `ex` into `st` the way `scrn import` does, returning the conflicts.
+    fn import_into(st: &mut State, ex: &Export, roots: &[WorkspaceRoot], merge: bool) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        let (sessions, dir_order) = localize(ex, roots, &mut conflicts);
+        apply(st, ex, &sessions, &dir_order, merge, &mut conflicts);
+        conflicts
+    }
+
+    #[test]
+    fn relative_to_roots_rewrites_only_paths_under_a_root() {
+        let roots = [root("work", "/home/me/work"), root("oss", "/src/oss")];
+        assert_eq!(relative_to_roots(Path::new("/home/me/work/api"), &roots), "work/api");
+        assert_eq!(relative_to_roots(Path::new("/src/oss/a/b"), &roots), "oss/a/b");
+        assert_eq!(relative_to_roots(Path::new("/src/oss"), &roots), "oss");
+        assert_eq!(relative_to_roots(Path::new("/home/me/workshop/x"), &roots), "/home/me/workshop/x");
+        assert_eq!(relative_to_roots(Path::new("/tmp/scratch"), &roots), "/tmp/scratch");
+    }
+
+    #[test]
+    fn export_then_import_moves_sessions_to_the_local_root() {
+        let mut st = State::default();
+        st.pins.insert("api".into());
+        st.constants = vec!["notes".into(), "mail".into()];
+        st.constant_commands.insert("notes".into(), "vim \"todo.md\"".into());
+        st.tags.insert("api".into(), vec!["backend".into(), "go".into()]);
+        st.dir_order.insert("work".into(), vec!["b".into(), "a".into()]);
+        st.sessions = vec![session("api", Some("/home/me/work/api")), session("scratch", Some("/tmp/scratch"))];
+
+        let text = render(&st, &[root("work", "/home/me/work")], 0);
+        let ex = parse(&text).unwrap();
+        let mut here = State::default();
+        let conflicts = import_into(&mut here, &ex, &[root("work", "/Users/me/code")], false);
+
+        assert!(conflicts.is_empty(), "{conflicts:?}");
+        assert_eq!(here.pins, st.pins);
+        assert_eq!(here.constants, st.constants);
+        assert_eq!(here.constant_commands, st.constant_commands);
+        assert_eq!(here.tags, st.tags);
+        assert_eq!(here.dir_order, st.dir_order);
+        let paths: Vec<_> = here.sessions.iter().map(|s| (s.name.as_str(), s.path.clone())).collect();
+        assert_eq!(
+            paths,
+            [("api", Some(PathBuf::from("/Users/me/code/api"))), ("scratch", Some(PathBuf::from("/tmp/scratch")))]
+        );
+    }
+
+    fn local_state() -> State {
+        let mut st = State::default();
+        st.pins.insert("api".into());
+        st.constants = vec!["web".into()];
+        st.constant_commands.insert("web".into(), "npm start".into());
+        st.sessions = vec![session("api", Some("/w/api"))];
+        st
+    }
+
+    fn imported() -> Export {
+        parse(
+            "pins = [\"web\"]\nconstants = [\"api\"]\n\
+             [commands]\nweb = \"yarn dev\"\n\
+             [dir_order]\nelsewhere = [\"x\"]\n\
+             [[sessions]]\nname = \"api\"\npath = \"/other/api\"\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn import_replaces_and_lists_what_it_overrode() {
+        let mut st = local_state();
+        let conflicts = import_into(&mut st, &imported(), &[root("w", "/w"), root("v", "/v")], false);
+        assert_eq!(
+            conflicts,
+            [
+                "dir order for 'elsewhere': no such workspace root, skipped",
+                "command for 'web': 'npm start' here, 'yarn dev' imported; using imported",
+                "session 'api': /w/api here, /other/api imported; using imported",
+            ]
+        );
+        assert_eq!(st.pins, HashSet::from(["web".to_string()]));
+        assert_eq!(st.constants, ["api"]);
+        assert_eq!(st.constant_commands["web"], "yarn dev");
+        assert_eq!(st.sessions[0].path, Some(PathBuf::from("/other/api")));
+    }
+
+    #[test]
+    fn import_merge_keeps_local_and_lists_disagreements() {
+        let mut st = local_state();
+        let conflicts = import_into(&mut st, &imported(), &[root("w", "/w"), root("v", "/v")], true);
+        assert_eq!(
+            conflicts,
+            [
+                "dir order for 'elsewhere': no such workspace root, skipped",
+                "'web' is a constant here but pinned in the import; kept local",
+                "'api' is pinned here but a constant in the import; kept local",
+                "command for 'web': 'npm start' here, 'yarn dev' imported; kept local",
+                "session 'api': /w/api here, /other/api imported; kept local",
+            ]
+        );
+        assert_eq!(st.pins, HashSet::from(["api".to_string()]));
+        assert_eq!(st.constants, ["web"]);
+        assert_eq!(st.constant_commands["web"], "npm start");
+        assert_eq!(st.sessions[0].path, Some(PathBuf::from("/w/api")));
+    }
+}
@@ -9,7 +9,8 @@ use ratatui::widgets::{
 };
 use ratatui::Frame;
 
-use crate::app::{fuzzy_match, App, ListItem, Mode};
+use crate::app::{App, ListItem, Mode};
+use crate::monitor::Badge;
 
 pub const ROW_HEIGHT: u16 = 1;
 
@@ -106,6 +107,52 @@ fn session_prefix_width(session: &crate::screen::Session, app: &App) -> usize {
     2
 }
 
+/// ` #tag1 #tag2` for a tagged session/repo, empty otherwise.
+fn tag_suffix(app: &App, name: &str) -> String {
+    app.tags
+        .get(name)
+        .map(|tags| tags.iter().map(|t| format!(" #{t}")).collect())
+        .unwrap_or_default()
+}
+
+/// Spinner shown in the title until the first refresh lands.
+fn loading_indicator(app: &App) -> String {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    match app.loading_since {
+        Some(since) => {
+            let frame = (since.elapsed().as_millis() / 100) as usize % FRAMES.len();
+            format!("{} loading ", FRAMES[frame])
+        }
+        None => String::new(),
+    }
+}
+
+/// Icon for a repo's root marker. Git is the common case and gets none.
+fn marker_icon(marker: Option<&str>) -> Option<&'static str> {
+    Some(match marker? {
+        ".git" => return None,
+        ".hg" => "\u{263f}",
+        ".jj" => "\u{25c7}",
+        "Cargo.toml" => "\u{2699}",
+        "package.json" => "\u{2b22}",
+        "go.mod" => "\u{25c8}",
+        _ => "\u{2022}",
+    })
+}
+
+/// Activity badge for a running session: `○` quiet, `●` new output, `!` bell
+/// (watched sessions only), `◔` waiting for a command to finish.
+fn watch_badge(app: &App, session: Option<&crate::screen::Session>, bg: Color) -> Option<Span<'static>> {
+    let badge = app.monitor.badge(&session?.pid_name)?;
+    let (text, style) = match badge {
+        Badge::Quiet => (" \u{25cb}", Style::default().fg(DIM).bg(bg)),
+        Badge::Activity => (" \u{25cf}", Style::default().fg(MATCH_FG).bg(bg)),
+        Badge::Bell => (" !", Style::default().fg(STATUS_ERR).bg(bg).add_modifier(Modifier::BOLD)),
+        Badge::Running => (" \u{25d4}", Style::default().fg(ACCENT).bg(bg)),
+    };
+    Some(Span::styled(text, style))
+}
+
 fn truncate(s: &str, max: usize) -> String {
     let count = s.chars().count();
     if count <= max {
@@ -193,6 +240,50 @@ pub fn draw(f: &mut Frame, app: &mut App) {
             dim_background(f);
             draw_command_modal(f, app);
         }
+        Mode::FinishResult => {
+            dim_background(f);
+            draw_finish_result(f, app);
+        }
+        Mode::EditingTags => {
+            dim_background(f);
+            draw_tags_modal(f, app);
+        }
+        Mode::CreatingWorktree => {
+            dim_background(f);
+            draw_worktree_modal(f, app);
+        }
+        Mode::ConfirmRemoveWorktree => {
+            dim_background(f);
+            draw_remove_worktree_modal(f, app);
+        }
+        Mode::ConfirmRecreate => {
+            dim_background(f);
+            draw_recreate_modal(f, app);
+        }
+        Mode::ConfirmTrust => {
+            dim_background(f);
+            draw_trust_modal(f, app);
+        }
+        Mode::RestorePreview => {
+            dim_background(f);
+            draw_restore_modal(f, app);
+        }
+        Mode::Archives => {
+            dim_background(f);
+            draw_archives_modal(f, app);
+        }
+        Mode::ContentSearch => {
+            dim_background(f);
+            draw_content_search_modal(f, app);
+        }
+        Mode::ContentResults => {
+            dim_background(f);
+            draw_content_results(f, app);
+        }
+        Mode::ArchiveView => {
+            dim_background(f);
+            draw_archive_view(f, app);
+        }
         _ => {}
     }
 
@@ -226,9 +317,18 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
         let mut max = MIN_NAME_W as usize;
         for item in &app.display_items {
             let n = match item {
-                ListItem::SessionItem(s) => session_prefix_width(s, app) + s.name.chars().count(),
-                ListItem::TreeRepo { name, prefix, .. } => {
-                    2 + prefix.chars().count() + name.chars().count()
+                ListItem::SessionItem(s) => {
+                    session_prefix_width(s, app)
+                        + s.name.chars().count()
+                        + tag_suffix(app, &s.name).chars().count()
+                        + if app.monitor.badge(&s.pid_name).is_some() { 2 } else { 0 }
+                }
+                ListItem::TreeRepo { name, prefix, session, label, marker, .. } => {
+                    2 + prefix.chars().count()
+                        + label.as_deref().unwrap_or(name).chars().count()
+                        + marker_icon(marker.as_deref()).map_or(0, |_| 2)
+                        + tag_suffix(app, name).chars().count()
+                        + if session.as_ref().is_some_and(|s| app.monitor.badge(&s.pid_name).is_some()) { 2 } else { 0 }
                 }
                 ListItem::TreeDir { name, descendant_repos, descendant_open, folded, .. } => {
                     let name_w = name.chars().count().min(MAX_DIR_NAME_CHARS);
@@ -353,6 +453,9 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
                 name,
                 session,
                 prefix,
+                label,
+                color,
+                marker,
                 ..
             } => {
                 let is_constant = app.constants.iter().any(|n| n == name);
@@ -367,45 +470,59 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
                 let display_prefix = if is_constant { "" } else { prefix.as_str() };
                 let used_prefix = 2 + display_prefix.chars().count();
                 let has_session = session.is_some();
-                let name_fg = if has_session { GREEN } else { REPO_FG };
+                // A repo colour replaces the open/closed colour; open repos go bold instead
+                let name_fg = match color {
+                    Some((r, g, b)) => Color::Rgb(*r, *g, *b),
+                    None if has_session => GREEN,
+                    None => REPO_FG,
+                };
+                let name_mod = if color.is_some() && has_session { Modifier::BOLD } else { Modifier::empty() };
 
+                let shown = label.as_deref().unwrap_or(name);
                 let max_name_avail = name_chars.saturating_sub(used_prefix);
-                let name_text = truncate(name, max_name_avail);
+                let name_text = truncate(shown, max_name_avail);
 
                 let mut spans: Vec<Span> = Vec::new();
                 spans.push(Span::styled("  ".to_string(), Style::default().fg(DIM).bg(bg)));
                 spans.push(Span::styled(display_prefix, Style::default().fg(TREE_GUIDE).bg(bg)));
 
-                if !app.search_input.is_empty() {
-                    if let Some((positions, _)) = fuzzy_match(name, &app.search_input) {
-                        let max_pos = name_text.chars().count();
-                        let highlight_set: std::collections::HashSet<usize> =
-                            positions.into_iter().filter(|&p| p < max_pos).collect();
-                        let normal_style = Style::default().fg(name_fg).bg(bg);
-                        let match_style = Style::default()
-                            .fg(MATCH_FG)
-                            .bg(bg)
-                            .add_modifier(Modifier::BOLD);
-                        let mut current = String::new();
-                        let mut current_is_match = false;
-                        for (ci, ch) in name_text.chars().enumerate() {
-                            let is_match = highlight_set.contains(&ci);
-                            if is_match != current_is_match && !current.is_empty() {
-                                let style = if current_is_match { match_style } else { normal_style };
-                                spans.push(Span::styled(std::mem::take(&mut current), style));
-                            }
-                            current.push(ch);
-                            current_is_match = is_match;
-                        }
-                        if !current.is_empty() {
+                let positions = app.search_query.highlight(shown);
+                if !positions.is_empty() {
+                    let max_pos = name_text.chars().count();
+                    let highlight_set: std::collections::HashSet<usize> =
+                        positions.into_iter().filter(|&p| p < max_pos).collect();
+                    let normal_style = Style::default().fg(name_fg).bg(bg).add_modifier(name_mod);
+                    let match_style = Style::default()
+                        .fg(MATCH_FG)
+                        .bg(bg)
+                        .add_modifier(Modifier::BOLD);
+                    let mut current = String::new();
+                    let mut current_is_match = false;
+                    for (ci, ch) in name_text.chars().enumerate() {
+                        let is_match = highlight_set.contains(&ci);
+                        if is_match != current_is_match && !current.is_empty() {
                             let style = if current_is_match { match_style } else { normal_style };
-                            spans.push(Span::styled(current, style));
+                            spans.push(Span::styled(std::mem::take(&mut current), style));
                         }
-                    } else {
-                        spans.push(Span::styled(name_text, Style::default().fg(name_fg).bg(bg)));
+                        current.push(ch);
+                        current_is_match = is_match;
+                    }
+                    if !current.is_empty() {
+                        let style = if current_is_match { match_style } else { normal_style };
+                        spans.push(Span::styled(current, style));
                     }
                 } else {
-                    spans.push(Span::styled(name_text, Style::default().fg(name_fg).bg(bg)));
+                    spans.push(Span::styled(name_text, Style::default().fg(name_fg).bg(bg).add_modifier(name_mod)));
+                }
+                if let Some(icon) = marker_icon(marker.as_deref()) {
+                    spans.push(Span::styled(format!(" {icon}"), Style::default().fg(DIM).bg(bg)));
+                }
+                let tags = tag_suffix(app, name);
+                if !tags.is_empty() {
+                    spans.push(Span::styled(tags, Style::default().fg(DIM).bg(bg)));
+                }
+                if let Some(badge) = watch_badge(app, session.as_ref(), bg) {
+                    spans.push(badge);
                 }
 
                 let cells = vec![Cell::from(Line::from(spans))];
@@ -448,37 +565,41 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
                     Style::default().fg(prefix_fg).bg(bg),
                 )];
 
-                if !app.search_input.is_empty() {
-                    if let Some((positions, _)) = fuzzy_match(&session.name, &app.search_input) {
-                        let max_pos = name_text.chars().count();
-                        let highlight_set: std::collections::HashSet<usize> =
-                            positions.into_iter().filter(|&p| p < max_pos).collect();
-                        let normal_style = Style::default().fg(name_fg).bg(bg);
-                        let match_style = Style::default()
-                            .fg(MATCH_FG)
-                            .bg(bg)
-                            .add_modifier(Modifier::BOLD);
-                        let mut current = String::new();
-                        let mut current_is_match = false;
-                        for (ci, ch) in name_text.chars().enumerate() {
-                            let is_match = highlight_set.contains(&ci);
-                            if is_match != current_is_match && !current.is_empty() {
-                                let style = if current_is_match { match_style } else { normal_style };
-                                spans.push(Span::styled(std::mem::take(&mut current), style));
-                            }
-                            current.push(ch);
-                            current_is_match = is_match;
-                        }
-                        if !current.is_empty() {
+                let positions = app.search_query.highlight(&session.name);
+                if !positions.is_empty() {
+                    let max_pos = name_text.chars().count();
+                    let highlight_set: std::collections::HashSet<usize> =
+                        positions.into_iter().filter(|&p| p < max_pos).collect();
+                    let normal_style = Style::default().fg(name_fg).bg(bg);
+                    let match_style = Style::default()
+                        .fg(MATCH_FG)
+                        .bg(bg)
+                        .add_modifier(Modifier::BOLD);
+                    let mut current = String::new();
+                    let mut current_is_match = false;
+                    for (ci, ch) in name_text.chars().enumerate() {
+                        let is_match = highlight_set.contains(&ci);
+                        if is_match != current_is_match && !current.is_empty() {
                             let style = if current_is_match { match_style } else { normal_style };
-                            spans.push(Span::styled(current, style));
+                            spans.push(Span::styled(std::mem::take(&mut current), style));
                         }
-                    } else {
-                        spans.push(Span::styled(name_text, Style::default().fg(name_fg).bg(bg)));
+                        current.push(ch);
+                        current_is_match = is_match;
+                    }
+                    if !current.is_empty() {
+                        let style = if current_is_match { match_style } else { normal_style };
+                        spans.push(Span::styled(current, style));
                     }
                 } else {
                     spans.push(Span::styled(name_text, Style::default().fg(name_fg).bg(bg)));
                 }
+                let tags = tag_suffix(app, &session.name);
+                if !tags.is_empty() {
+                    spans.push(Span::styled(tags, Style::default().fg(DIM).bg(bg)));
+                }
+                if let Some(badge) = watch_badge(app, Some(session), bg) {
+                    spans.push(badge);
+                }
 
                 let cells = vec![Cell::from(Line::from(spans))];
                 Row::new(cells).height(ROW_HEIGHT).style(Style::default().fg(FG).bg(bg))
@@ -505,6 +626,10 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
                 format!("v{} ", env!("CARGO_PKG_VERSION")),
                 Style::default().fg(VERSION_FG).bg(BASE_BG),
             ),
+            Span::styled(
+                loading_indicator(app),
+                Style::default().fg(DIM).bg(BASE_BG),
+            ),
         ]))
         ;
 
@@ -526,19 +651,32 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
         hints.push(("`","Back"));
     }
     hints.push(("/","Search"));
+    hints.push(("F","Output"));
     hints.push(("c","New"));
     hints.push(("x","Kill"));
     hints.push(("p","Pin"));
+    hints.push(("w","Watch"));
     hints.push(("C","Const"));
     if on_constant {
         hints.push(("e", "Cmd"));
     }
-    if app.workspace_tree.as_ref().is_some_and(|t| t.children.iter().any(|c| !c.is_repo)) {
+    match app.selected_display_item() {
+        Some(ListItem::TreeRepo { worktree_of: Some(_), .. }) => {
+            hints.push(("W", "Worktree"));
+            hints.push(("D", "Remove"));
+        }
+        Some(ListItem::TreeRepo { marker, .. }) if marker.as_deref() == Some(".git") => {
+            hints.push(("W", "Worktree"));
+        }
+        _ => {}
+    }
+    if app.root_nodes().iter().any(|(_, t)| t.children.iter().any(|c| !c.is_repo)) {
         hints.push(("O", "Order"));
     }
     if !app.constants.is_empty() {
         hints.push(("R", "Reorder"));
     }
+    hints.push(("a","Archive"));
     hints.push(("q","Quit"));
 
     for (i, (key, desc)) in hints.iter().enumerate() {
@@ -546,7 +684,7 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
             bottom_left_spans.push(Span::styled(" ", Style::default().fg(DIM).bg(BASE_BG)));
         }
         bottom_left_spans.push(Span::styled(
-            format!("{key}"),
+            key.to_string(),
             Style::default().fg(ACCENT).bg(BASE_BG),
         ));
         bottom_left_spans.push(Span::styled(
@@ -566,12 +704,14 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
     if !app.status_msg.is_empty() {
         let is_error = app.status_msg.starts_with("Error");
         let fg = if is_error { STATUS_ERR } else { STATUS_OK };
+        let flashing = app.status_flash_until.is_some_and(|t| std::time::Instant::now() < t);
+        let modifier = if flashing { Modifier::REVERSED | Modifier::BOLD } else { Modifier::empty() };
         if !bottom_right_spans.is_empty() {
             bottom_right_spans.push(Span::styled(" ", Style::default().bg(BASE_BG)));
         }
         bottom_right_spans.push(Span::styled(
             format!(" {} ", app.status_msg),
-            Style::default().fg(fg).bg(BASE_BG),
+            Style::default().fg(fg).bg(BASE_BG).add_modifier(modifier),
         ));
     }
     if !bottom_right_spans.is_empty() {
@@ -581,6 +721,8 @@ fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
     if app.selectable_indices.is_empty() {
         let msg = if !app.search_input.is_empty() {
             "  No matches"
+        } else if app.loading_since.is_some() {
+            "  Loading sessions\u{2026}"
         } else {
             "  No screen sessions found. Press 'c' to create one."
         };
@@ -671,6 +813,12 @@ fn draw_search_bar(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(DIM).bg(SEARCH_BG),
         ));
     }
+    if let Some(err) = &app.search_error {
+        spans.push(Span::styled(
+            format!("  {err}"),
+            Style::default().fg(STATUS_ERR).bg(SEARCH_BG),
+        ));
+    }
     spans.push(Span::styled(
         format!("  ({} matches)", app.selectable_indices.len()),
         Style::default().fg(COUNT_FG).bg(SEARCH_BG),
@@ -1050,10 +1198,310 @@ fn draw_command_modal(f: &mut Frame, app: &App) {
     );
 }
 
+fn draw_tags_modal(f: &mut Frame, app: &App) {
+    let name = app.selected_item_name().unwrap_or_default();
+    let area = f.area();
+    let width = 60u16.min(area.width.saturating_sub(4));
+    let height = 5u16;
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            format!(" Tags: {name} "),
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Span::styled(
+            " Enter save  Esc cancel ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let max_chars = inner.width.saturating_sub(2) as usize;
+    let display = visible_input(&app.create_input, app.cursor_pos, max_chars);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            " Space-separated, search with tag:<name>",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )),
+        Line::from(Span::styled(
+            format!(" {display}"),
+            Style::default().fg(ACCENT).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+// ── Worktree modals ──────────────────────────────────────────
+
+fn draw_worktree_modal(f: &mut Frame, app: &App) {
+    let (name, repo) = app.worktree_repo.clone().unwrap_or_default();
+    let area = f.area();
+    let width = 60u16.min(area.width.saturating_sub(4));
+    let height = 5u16;
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            format!(" New Worktree: {name} "),
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Span::styled(
+            " Enter create  Esc cancel ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let max_chars = inner.width.saturating_sub(2) as usize;
+    let display = visible_input(&app.create_input, app.cursor_pos, max_chars);
+    let branch = app.create_input.trim();
+    let hint = if branch.is_empty() {
+        " Branch (new or existing)".to_string()
+    } else {
+        let path = crate::workspace::worktree_path(&repo, branch);
+        let shown = path
+            .strip_prefix(repo.parent().unwrap_or(&repo))
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        format!(" Branch \u{2192} {shown}")
+    };
+
+    let lines = vec![
+        Line::from(Span::styled(
+            truncate(&hint, inner.width as usize),
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )),
+        Line::from(Span::styled(
+            format!(" {display}"),
+            Style::default().fg(ACCENT).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+fn draw_remove_worktree_modal(f: &mut Frame, app: &App) {
+    let name = app
+        .worktree_remove
+        .as_ref()
+        .map(|(name, _, _)| name.clone())
+        .unwrap_or_default();
+    let has_session = app.all_sessions.iter().any(|s| s.name == name);
+
+    let area = f.area();
+    let width = 56u16.min(area.width.saturating_sub(4));
+    let height = 5u16;
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(KILL_BORDER).bg(KILL_BG))
+        .style(Style::default().fg(FG).bg(KILL_BG))
+        .title(Span::styled(
+            " Remove Worktree ",
+            Style::default()
+                .fg(KILL_TITLE)
+                .bg(KILL_BG)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let question = if has_session {
+        format!(" Remove '{name}' and kill its session?")
+    } else {
+        format!(" Remove worktree '{name}'?")
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            truncate(&question, inner.width as usize),
+            Style::default().fg(FG_BRIGHT).bg(KILL_BG),
+        )),
+        Line::from(Span::styled(
+            " y: confirm  Esc: cancel",
+            Style::default().fg(DIM).bg(KILL_BG),
+        )),
+    ];
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(KILL_BG)),
+        inner,
+    );
+}
+
+// ── Recreate vanished session modal ──────────────────────────
+
+fn draw_recreate_modal(f: &mut Frame, app: &App) {
+    let name = app
+        .recreate_target
+        .as_ref()
+        .map(|(name, _)| name.as_str())
+        .unwrap_or("");
+
+    let area = f.area();
+    let width = 56u16.min(area.width.saturating_sub(4));
+    let height = 6u16;
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            " Session Gone ",
+            Style::default()
+                .fg(MODAL_TITLE)
+                .bg(MODAL_BG)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            truncate(&format!(" '{name}' ended before it could be attached."), inner.width as usize),
+            Style::default().fg(FG).bg(MODAL_BG),
+        )),
+        Line::from(Span::styled(
+            " Recreate it in its repo?",
+            Style::default().fg(FG_BRIGHT).bg(MODAL_BG),
+        )),
+        Line::from(Span::styled(
+            " y/Enter: recreate  n/Esc: cancel",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )),
+    ];
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+// ── Trust repo config modal ──────────────────────────────────
+
+fn draw_trust_modal(f: &mut Frame, app: &App) {
+    let Some(prompt) = app.trust_prompt.as_ref() else {
+        return;
+    };
+
+    // What the file would run, one line each
+    let mut runs: Vec<String> = Vec::new();
+    if let Some(ref command) = prompt.config.command {
+        runs.push(format!("run: {command}"));
+    }
+    for (i, w) in prompt.config.windows.iter().enumerate() {
+        if let Some(ref command) = w.command {
+            runs.push(format!("window {}: {command}", i + 1));
+        }
+    }
+    for (key, value) in &prompt.config.env {
+        runs.push(format!("env: {key}={value}"));
+    }
+
+    let area = f.area();
+    let width = 64u16.min(area.width.saturating_sub(4));
+    let max_runs = (area.height.saturating_sub(9) as usize).max(1);
+    let shown = runs.len().min(max_runs);
+    let height = (shown as u16 + 7).min(area.height);
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            " Trust .scrn.toml? ",
+            Style::default()
+                .fg(MODAL_TITLE)
+                .bg(MODAL_BG)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let width = inner.width as usize;
+    let mut lines = vec![
+        Line::from(Span::styled(
+            truncate(&format!(" {}/.scrn.toml would:", prompt.repo.display()), width),
+            Style::default().fg(FG).bg(MODAL_BG),
+        )),
+        Line::from(""),
+    ];
+    for run in &runs[..shown] {
+        lines.push(Line::from(Span::styled(
+            truncate(&format!("   {run}"), width),
+            Style::default().fg(FG_BRIGHT).bg(MODAL_BG),
+        )));
+    }
+    if shown < runs.len() {
+        lines.push(Line::from(Span::styled(
+            format!("   \u{2026} {} more", runs.len() - shown),
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        truncate(" y: trust and open  n: open without it  Esc: cancel", width),
+        Style::default().fg(DIM).bg(MODAL_BG),
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
 // ── Directory order modal ────────────────────────────────────
 
 fn draw_ordering_modal(f: &mut Frame, app: &App) {
     let area = f.area();
+    let title = if app.workspace_roots.len() > 1 {
+        format!(" Order Directories \u{2014} {} ", app.ordering_root)
+    } else {
+        " Order Directories ".to_string()
+    };
     let n = app.ordering_items.len() as u16;
     let height = (n * 2 + 3).min(area.height.saturating_sub(4));
     let width = app.ordering_items.iter()
@@ -1074,7 +1522,7 @@ fn draw_ordering_modal(f: &mut Frame, app: &App) {
         .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
         .style(Style::default().fg(FG).bg(MODAL_BG))
         .title(Span::styled(
-            " Order Directories ",
+            title,
             Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
         ))
         .title_bottom(Line::from(Span::styled(
@@ -1159,5 +1607,355 @@ fn draw_constant_ordering_modal(f: &mut Frame, app: &App) {
     );
 }
 
+// ── Restore preview modal ────────────────────────────────────
+
+fn draw_restore_modal(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let n = app.restore_candidates.len() as u16;
+    let height = (n + 4).min(area.height.saturating_sub(4));
+    let width = 64u16.min(area.width.saturating_sub(4));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            " Restore Sessions ",
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Space toggle  a all  Enter restore  Esc skip ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    // Keep the selection in view when the list is taller than the modal
+    let visible = inner.height.saturating_sub(1) as usize;
+    let skip = app.restore_selected.saturating_sub(visible.saturating_sub(1));
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    for (i, (saved, ticked)) in app.restore_candidates.iter().enumerate().skip(skip) {
+        let selected = i == app.restore_selected;
+        let bg = if selected { HIGHLIGHT_BG } else { MODAL_BG };
+        let fg = if selected { FG_BRIGHT } else { FG };
+        let check = if *ticked { " [x] " } else { " [ ] " };
+        let windows = match saved.windows.len() {
+            0 | 1 => String::new(),
+            n => format!("  {n} windows"),
+        };
+        let dir = saved
+            .path
+            .as_ref()
+            .or_else(|| saved.windows.first().and_then(|w| w.cwd.as_ref()))
+            .map(|p| format!("  {}", p.display()))
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(check, Style::default().fg(ACCENT).bg(bg)),
+            Span::styled(saved.name.clone(), Style::default().fg(fg).bg(bg)),
+            Span::styled(windows, Style::default().fg(GREEN).bg(bg)),
+            Span::styled(truncate(&dir, width as usize / 2), Style::default().fg(DIM).bg(bg)),
+        ]));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+// ── Archive browser ─────────────────────────────────────────
+
+fn draw_archives_modal(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let n = app.archives.len() as u16;
+    let height = (n + 3).min(area.height.saturating_sub(4));
+    let width = 72u16.min(area.width.saturating_sub(4));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            " Archived Sessions ",
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Enter view  u undo kill  x delete  Esc close ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let visible = inner.height.saturating_sub(1) as usize;
+    let skip = app.archive_selected.saturating_sub(visible.saturating_sub(1));
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    for (i, a) in app.archives.iter().enumerate().skip(skip) {
+        let selected = i == app.archive_selected;
+        let bg = if selected { HIGHLIGHT_BG } else { MODAL_BG };
+        let fg = if selected { FG_BRIGHT } else { FG };
+        let prefix = if selected { "  \u{2588} " } else { "    " };
+        let dir = a
+            .cwd
+            .as_ref()
+            .map(|p| format!("  {}", p.display()))
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(prefix, Style::default().fg(ACCENT).bg(bg)),
+            Span::styled(
+                crate::logging::format_utc(a.timestamp),
+                Style::default().fg(COUNT_FG).bg(bg),
+            ),
+            Span::styled(format!("  {}", a.name), Style::default().fg(fg).bg(bg)),
+            Span::styled(truncate(&dir, width as usize / 3), Style::default().fg(DIM).bg(bg)),
+        ]));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+fn draw_archive_view(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let width = area.width.saturating_sub(8).min(140);
+    let height = area.height.saturating_sub(4);
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let name = app
+        .archives
+        .get(app.archive_selected)
+        .map(|a| a.name.as_str())
+        .unwrap_or("");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            format!(" {name} "),
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " j/k scroll  u/d page  g/G top/bottom  U undo kill  Esc back ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    // `archive_scroll` is the bottom-most visible line
+    let visible = inner.height as usize;
+    let start = (app.archive_scroll + 1).saturating_sub(visible);
+    let lines: Vec<Line> = app
+        .archive_lines
+        .iter()
+        .skip(start)
+        .take(visible)
+        .map(|l| {
+            let fg = if l.starts_with("\u{2500}\u{2500} window") { SECTION_FG } else { FG };
+            Line::from(Span::styled(l.clone(), Style::default().fg(fg).bg(MODAL_BG)))
+        })
+        .collect();
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+// ── Finished command ────────────────────────────────────────
+
+fn draw_finish_result(f: &mut Frame, app: &App) {
+    let Some((name, _, result)) = app.finished_results.first() else {
+        return;
+    };
+    let area = f.area();
+    let width = area.width.saturating_sub(8).min(100);
+    let height = (result.tail.len() as u16 + 5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            format!(" Finished: {name} "),
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Enter attach  Esc close ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
 
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
 
+    let max_chars = inner.width.saturating_sub(1) as usize;
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!(" {}", truncate(&result.command, max_chars.saturating_sub(12))),
+                Style::default().fg(ACCENT).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  ≥{}", crate::monitor::format_elapsed(result.elapsed)),
+                Style::default().fg(DIM).bg(MODAL_BG),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    lines.extend(result.tail.iter().map(|l| {
+        Line::from(Span::styled(
+            format!(" {}", truncate(l, max_chars)),
+            Style::default().fg(FG).bg(MODAL_BG),
+        ))
+    }));
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+// ── Scrollback search ───────────────────────────────────────
+
+fn draw_content_search_modal(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let width = 60u16.min(area.width.saturating_sub(4));
+    let height = 5u16;
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            " Search Output ",
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Span::styled(
+            " Enter search  Esc cancel ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        ));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let max_chars = inner.width.saturating_sub(2) as usize;
+    let display = visible_input(&app.create_input, app.cursor_pos, max_chars);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            " Text in any session's scrollback:",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )),
+        Line::from(Span::styled(
+            format!(" {display}"),
+            Style::default().fg(FG_BRIGHT).bg(MODAL_BG),
+        )),
+    ];
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
+
+fn draw_content_results(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let width = area.width.saturating_sub(8).min(140);
+    let n = app.content_results.len() as u16;
+    let height = (n + 3).min(area.height.saturating_sub(4));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let modal_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(MODAL_BORDER).bg(MODAL_BG))
+        .style(Style::default().fg(FG).bg(MODAL_BG))
+        .title(Span::styled(
+            format!(" '{}' \u{2014} {} matches ", app.content_query, app.content_results.len()),
+            Style::default().fg(MODAL_TITLE).bg(MODAL_BG).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Enter attach  / new search  Esc close ",
+            Style::default().fg(DIM).bg(MODAL_BG),
+        )));
+
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let name_w = app
+        .content_results
+        .iter()
+        .map(|m| m.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(24);
+    let visible = inner.height.saturating_sub(1) as usize;
+    let skip = app.content_selected.saturating_sub(visible.saturating_sub(1));
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    for (i, m) in app.content_results.iter().enumerate().skip(skip) {
+        let selected = i == app.content_selected;
+        let bg = if selected { HIGHLIGHT_BG } else { MODAL_BG };
+        let fg = if selected { FG_BRIGHT } else { FG };
+        let prefix = if selected { " \u{2588} " } else { "   " };
+        let name = truncate(&m.name, name_w);
+        let label = format!("{name:<name_w$} {:>3} ", format!("w{}", m.window));
+
+        // Keep the match in view: trim the line's head when it would scroll off
+        let budget = (inner.width as usize).saturating_sub(label.chars().count() + 4);
+        let skip_chars = (m.end + 8).saturating_sub(budget).min(m.start);
+        let text: Vec<char> = m.line.chars().skip(skip_chars).take(budget).collect();
+        let (start, end) = (m.start - skip_chars, (m.end - skip_chars).min(text.len()));
+        let before: String = text[..start.min(text.len())].iter().collect();
+        let hit: String = text[start.min(text.len())..end.max(start).min(text.len())].iter().collect();
+        let after: String = text[end.min(text.len())..].iter().collect();
+
+        lines.push(Line::from(vec![
+            Span::styled(prefix, Style::default().fg(ACCENT).bg(bg)),
+            Span::styled(label, Style::default().fg(GREEN).bg(bg)),
+            Span::styled(before, Style::default().fg(fg).bg(bg)),
+            Span::styled(
+                hit,
+                Style::default().fg(MATCH_FG).bg(bg).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(after, Style::default().fg(fg).bg(bg)),
+        ]));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(FG).bg(MODAL_BG)),
+        inner,
+    );
+}
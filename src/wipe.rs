@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::logging;
+use crate::monitor::format_elapsed;
+use crate::paths;
+use crate::screen;
+
+enum Problem {
+    /// The server is gone and only its socket is left.
+    Dead,
+    /// The server is running but doesn't answer `-Q`.
+    Unresponsive,
+}
+
+struct Entry {
+    pid_name: String,
+    problem: Problem,
+    socket: PathBuf,
+}
+
+impl Entry {
+    /// Time since screen last touched the socket.
+    fn age(&self) -> String {
+        fs::metadata(&self.socket)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .map(|d| format_elapsed(Duration::from_secs(d.as_secs())))
+            .unwrap_or_else(|| "?".to_string())
+    }
+
+    fn pid(&self) -> &str {
+        self.pid_name.split('.').next().unwrap_or("")
+    }
+
+    fn action(&self) -> String {
+        match self.problem {
+            Problem::Dead => format!("remove {}", self.socket.display()),
+            Problem::Unresponsive => format!("kill {} and remove its socket", self.pid()),
+        }
+    }
+}
+
+/// Dead sockets, and live sessions that don't answer, in `screen -ls` order.
+/// A socket screen calls dead whose PID is still a screen server counts as
+/// unresponsive: removing it would orphan the server. Anything else behind
+/// the PID is a reused PID, and only the socket is removed.
+fn find(report: &screen::SocketReport) -> Result<Vec<Entry>, String> {
+    let dir = report.dir.clone().unwrap_or_else(paths::socket_dir);
+    let entry = |pid_name: &str, problem| Entry {
+        pid_name: pid_name.to_string(),
+        problem,
+        socket: dir.join(pid_name),
+    };
+
+    let mut entries: Vec<Entry> = report
+        .dead
+        .iter()
+        .map(|p| {
+            let problem = if screen::server_running(p) { Problem::Unresponsive } else { Problem::Dead };
+            entry(p, problem)
+        })
+        .collect();
+
+    // Each probe can take the full query timeout, so ask them all at once.
+    let probes: Vec<_> = screen::list_sessions()?
+        .into_iter()
+        .map(|s| std::thread::spawn(move || (screen::responds(&s.pid_name), s.pid_name)))
+        .collect();
+    for probe in probes {
+        if let Ok((false, pid_name)) = probe.join() {
+            if screen::server_running(&pid_name) {
+                entries.push(entry(&pid_name, Problem::Unresponsive));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// `scrn wipe [--dry-run] [--kill]`: remove sockets left by dead sessions.
+/// Unresponsive sessions are listed, and only killed with `--kill`.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut dry_run = false;
+    let mut kill = false;
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" | "-n" => dry_run = true,
+            "--kill" => kill = true,
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+
+    let entries = find(&screen::socket_report()?)?;
+    if entries.is_empty() {
+        println!("No dead or unresponsive sessions.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for entry in &entries {
+        let label = match entry.problem {
+            Problem::Dead => "dead",
+            Problem::Unresponsive => "unresponsive",
+        };
+        let age = entry.age();
+        let outcome = if dry_run {
+            format!("would {}", entry.action())
+        } else if matches!(entry.problem, Problem::Unresponsive) && !kill {
+            format!("skipped, pass --kill to {}", entry.action())
+        } else {
+            let remove_socket = || match fs::remove_file(&entry.socket) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            };
+            let result = match entry.problem {
+                Problem::Dead => remove_socket(),
+                // Checked again: the server may have exited since `find`.
+                Problem::Unresponsive if !screen::server_running(&entry.pid_name) => {
+                    Err(format!("{} is no longer a screen server, not killed", entry.pid()))
+                }
+                // kill_session removes the socket from scrn's idea of the
+                // socket dir; screen's own may differ.
+                Problem::Unresponsive => screen::kill_session(&entry.pid_name).and_then(|()| remove_socket()),
+            };
+            match result {
+                Ok(()) => {
+                    logging::info(&format!("Wiped {label} session {}", entry.pid_name));
+                    format!("{}: done", entry.action())
+                }
+                Err(e) => {
+                    failed += 1;
+                    e
+                }
+            }
+        };
+        println!("{label:<12}  {:<28} {:>6}  {outcome}", entry.pid_name, age);
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} left in place"));
+    }
+    Ok(())
+}
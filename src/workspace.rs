@@ -1,19 +1,155 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
-#[derive(Clone, Debug)]
+use crate::config::{self, RepoConfig, WorkspaceRoot};
+use crate::glob::glob_match;
+use crate::logging::Logged;
+use crate::paths;
+use crate::statefile;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct TreeNode {
     pub name: String,
     pub path: PathBuf,
     pub is_repo: bool,
+    /// subdirectories, or for a git repo its linked worktrees
     pub children: Vec<TreeNode>,
+    /// parsed `.scrn.toml`, for repos that have one
+    pub repo_config: Option<RepoConfig>,
+    /// the root marker that made this a repo (`.git`, `Cargo.toml`, ...)
+    pub marker: Option<String>,
+}
+
+/// Files or directories that make a directory a project root, checked in
+/// order.
+pub const DEFAULT_MARKERS: &[&str] = &[".git", ".hg", ".jj", "Cargo.toml", "package.json", "go.mod"];
+
+/// Limits on how the workspace is walked, from the `[scan]` config section.
+#[derive(Clone, Debug)]
+pub struct ScanOptions {
+    /// Directory levels below a root to descend; repos directly in the root
+    /// are depth 1. `None` is unlimited.
+    pub max_depth: Option<usize>,
+    /// Gitignore-style patterns applied before each root's `.scrnignore`
+    pub ignore: Vec<String>,
+    /// Descend into symlinked directories. Links back into a directory
+    /// being scanned are skipped either way.
+    pub follow_symlinks: bool,
+    /// Project root markers; the first one present wins
+    pub markers: Vec<String>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            ignore: Vec::new(),
+            follow_symlinks: true,
+            markers: DEFAULT_MARKERS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+}
+
+/// Gitignore-style rules for directory paths relative to a root. A pattern
+/// without a `/` matches a name at any depth, a leading `/` anchors it to
+/// the root, `!` re-includes, and the last matching rule wins.
+struct IgnoreRules {
+    /// (negated, glob)
+    rules: Vec<(bool, String)>,
+}
+
+impl IgnoreRules {
+    fn new(opts: &ScanOptions, root: &Path) -> Self {
+        let mut rules = Self { rules: Vec::new() };
+        for pattern in &opts.ignore {
+            rules.push(pattern);
+        }
+        if let Ok(contents) = fs::read_to_string(root.join(".scrnignore")) {
+            for line in contents.lines() {
+                rules.push(line);
+            }
+        }
+        rules
+    }
+
+    fn push(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let pattern = pattern.trim_end_matches('/');
+        let glob = if let Some(anchored) = pattern.strip_prefix('/') {
+            anchored.to_string()
+        } else if pattern.contains('/') {
+            pattern.to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        if !glob.is_empty() {
+            self.rules.push((negated, glob));
+        }
+    }
+
+    /// `dir/**` also prunes `dir` itself: nothing under it would be kept.
+    fn is_ignored(&self, rel: &str) -> bool {
+        let under = format!("{rel}/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|(_, glob)| glob_match(glob, rel) || glob_match(glob, &under))
+            .is_some_and(|(negated, _)| !negated)
+    }
+}
+
+/// State for walking one root.
+struct Scan<'a> {
+    opts: &'a ScanOptions,
+    root: &'a Path,
+    rules: IgnoreRules,
+    /// canonical paths of the directories being descended, when following
+    /// symlinks, to catch links that loop back
+    ancestors: Vec<PathBuf>,
+}
+
+/// Scan every workspace root. A single root is the tree itself; several
+/// roots become top-level directory nodes, named after the root and kept in
+/// config order, under a synthetic root. Roots without repos are left out.
+pub fn scan_roots(roots: &[WorkspaceRoot], opts: &ScanOptions) -> Option<TreeNode> {
+    match roots {
+        [] => None,
+        [root] => Some(scan_tree(&root.path, opts)),
+        _ => {
+            let children = roots
+                .iter()
+                .map(|root| TreeNode {
+                    name: root.name.clone(),
+                    ..scan_tree(&root.path, opts)
+                })
+                .filter(|node| !node.children.is_empty())
+                .collect();
+            Some(TreeNode {
+                name: String::new(),
+                path: PathBuf::new(),
+                is_repo: false,
+                children,
+                repo_config: None,
+                marker: None,
+            })
+        }
+    }
 }
 
 /// Build a recursive tree of directories and repos under `dir`.
-/// Repo nodes (have .git) are leaves; non-repo dirs recurse.
-/// Hidden directories (`.` prefix) are skipped.
+/// Repo nodes (have a root marker) are leaves; other dirs recurse.
+/// Hidden directories (`.` prefix) are skipped, as are repos whose
+/// `.scrn.toml` sets `hidden = true` and paths matched by the ignore rules.
 /// Children sorted alphabetically (case-insensitive).
-pub fn scan_tree(dir: &Path) -> TreeNode {
+pub fn scan_tree(dir: &Path, opts: &ScanOptions) -> TreeNode {
     let name = dir
         .file_name()
         .and_then(|n| n.to_str())
@@ -24,21 +160,39 @@ pub fn scan_tree(dir: &Path) -> TreeNode {
         path: dir.to_path_buf(),
         is_repo: false,
         children: Vec::new(),
+        repo_config: None,
+        marker: None,
+    };
+    let mut scan = Scan {
+        opts,
+        root: dir,
+        rules: IgnoreRules::new(opts, dir),
+        ancestors: Vec::new(),
     };
-    scan_tree_recursive(dir, &mut root);
+    scan_tree_recursive(&mut scan, dir, &mut root, 1);
     root
 }
 
-fn scan_tree_recursive(dir: &Path, node: &mut TreeNode) {
+fn scan_tree_recursive(scan: &mut Scan, dir: &Path, node: &mut TreeNode, depth: usize) {
+    if scan.opts.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
     let entries = match fs::read_dir(dir) {
         Ok(entries) => entries,
         Err(_) => return,
     };
+    if scan.opts.follow_symlinks {
+        scan.ancestors.push(fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf()));
+    }
 
     let mut children: Vec<TreeNode> = Vec::new();
 
     for entry in entries.flatten() {
         let path = entry.path();
+        let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_link && !scan.opts.follow_symlinks {
+            continue;
+        }
         if !path.is_dir() {
             continue;
         }
@@ -58,6 +212,16 @@ fn scan_tree_recursive(dir: &Path, node: &mut TreeNode) {
         {
             continue;
         }
+        let rel = path.strip_prefix(scan.root).unwrap_or(&path).to_string_lossy().into_owned();
+        if scan.rules.is_ignored(&rel) {
+            continue;
+        }
+        // A link to a directory that is already being scanned would loop
+        if is_link
+            && fs::canonicalize(&path).is_ok_and(|target| scan.ancestors.contains(&target))
+        {
+            continue;
+        }
 
         let child_name = entry
             .file_name()
@@ -65,13 +229,25 @@ fn scan_tree_recursive(dir: &Path, node: &mut TreeNode) {
             .unwrap_or("")
             .to_string();
 
-        if path.join(".git").exists() {
+        let marker = scan.opts.markers.iter().find(|m| path.join(m).exists());
+        if let Some(marker) = marker {
             // Repo leaf node
+            let repo_config = config::load_repo_config(&path);
+            if repo_config.as_ref().is_some_and(|c| c.hidden) {
+                continue;
+            }
+            let worktrees = if marker == ".git" {
+                scan_worktrees(&path, &child_name)
+            } else {
+                Vec::new()
+            };
             children.push(TreeNode {
                 name: child_name,
                 path,
                 is_repo: true,
-                children: Vec::new(),
+                children: worktrees,
+                repo_config,
+                marker: Some(marker.clone()),
             });
         } else {
             // Directory node — recurse
@@ -80,8 +256,10 @@ fn scan_tree_recursive(dir: &Path, node: &mut TreeNode) {
                 path: path.clone(),
                 is_repo: false,
                 children: Vec::new(),
+                repo_config: None,
+                marker: None,
             };
-            scan_tree_recursive(&path, &mut child);
+            scan_tree_recursive(scan, &path, &mut child, depth + 1);
             // Only include directory if it has descendants
             if !child.children.is_empty() {
                 children.push(child);
@@ -89,7 +267,302 @@ fn scan_tree_recursive(dir: &Path, node: &mut TreeNode) {
         }
     }
 
+    if scan.opts.follow_symlinks {
+        scan.ancestors.pop();
+    }
+
     // Sort alphabetically, case-insensitive
-    children.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    children.sort_by_key(|a| a.name.to_lowercase());
     node.children = children;
 }
+
+/// Session name for a worktree: `<repo>@<branch>`, with `/` in the branch
+/// replaced since screen session names can't contain it.
+pub fn worktree_session_name(repo_name: &str, branch: &str) -> String {
+    format!("{repo_name}@{}", branch.replace('/', "-"))
+}
+
+/// Linked worktrees of a git repo, as repo nodes named `<repo>@<branch>`.
+/// `git worktree list` only runs for repos that have any (`.git/worktrees/`).
+fn scan_worktrees(repo: &Path, repo_name: &str) -> Vec<TreeNode> {
+    let has_linked = fs::read_dir(repo.join(".git").join("worktrees"))
+        .is_ok_and(|mut entries| entries.next().is_some());
+    if !has_linked {
+        return Vec::new();
+    }
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["worktree", "list", "--porcelain"])
+        .logged_output()
+    else {
+        return Vec::new();
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut worktrees: Vec<TreeNode> = text
+        .split("\n\n")
+        // The first entry is the main checkout
+        .skip(1)
+        .filter_map(|block| {
+            let mut path = None;
+            let mut branch = None;
+            for line in block.lines() {
+                if let Some(p) = line.strip_prefix("worktree ") {
+                    path = Some(PathBuf::from(p));
+                } else if let Some(b) = line.strip_prefix("branch ") {
+                    branch = Some(b.strip_prefix("refs/heads/").unwrap_or(b).to_string());
+                } else if line.starts_with("prunable") || line == "bare" {
+                    return None;
+                }
+            }
+            let path = path.filter(|p| p.is_dir())?;
+            // Detached worktrees go by their directory name
+            let branch = branch.or_else(|| Some(path.file_name()?.to_str()?.to_string()))?;
+            Some(TreeNode {
+                name: worktree_session_name(repo_name, &branch),
+                repo_config: config::load_repo_config(&path),
+                path,
+                is_repo: true,
+                children: Vec::new(),
+                marker: Some(".git".to_string()),
+            })
+        })
+        .collect();
+    worktrees.sort_by_key(|w| w.name.to_lowercase());
+    worktrees
+}
+
+/// Where `scrn` puts a new worktree: `<repo>.worktrees/<branch>` next to the
+/// repo, which the scan skips.
+pub fn worktree_path(repo: &Path, branch: &str) -> PathBuf {
+    let name = repo.file_name().and_then(|n| n.to_str()).unwrap_or("repo");
+    repo.with_file_name(format!("{name}.worktrees")).join(branch)
+}
+
+/// `git worktree add`, creating `branch` unless it already exists.
+pub fn add_worktree(repo: &Path, branch: &str, path: &Path) -> Result<(), String> {
+    let exists = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{branch}"))
+        .logged_output()
+        .is_ok_and(|o| o.status.success());
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo).args(["worktree", "add"]);
+    if exists {
+        cmd.arg(path).arg(branch);
+    } else {
+        cmd.args(["-b", branch]).arg(path);
+    }
+    run_git(cmd, "git worktree add")
+}
+
+/// `git worktree remove`. Refuses, like git, when the worktree has changes.
+pub fn remove_worktree(repo: &Path, path: &Path) -> Result<(), String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo).args(["worktree", "remove"]).arg(path);
+    run_git(cmd, "git worktree remove")
+}
+
+fn run_git(mut cmd: Command, what: &str) -> Result<(), String> {
+    let output = cmd.logged_output().map_err(|e| format!("{what}: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // git prints progress first; the reason is the fatal/error line
+    let reason = stderr
+        .lines()
+        .find_map(|l| l.strip_prefix("fatal: ").or_else(|| l.strip_prefix("error: ")))
+        .or_else(|| stderr.lines().rev().find(|l| !l.trim().is_empty()))
+        .unwrap_or("failed")
+        .trim();
+    Err(format!("{what}: {reason}"))
+}
+
+fn cache_path() -> PathBuf {
+    paths::cache_dir().join("tree")
+}
+
+/// Identifies the roots and options a cached tree was scanned with.
+fn cache_key(roots: &[WorkspaceRoot], opts: &ScanOptions) -> String {
+    format!("{:016x}", statefile::fnv1a(format!("{roots:?}{opts:?}").as_bytes()))
+}
+
+/// Write the tree for the next startup. The first line is
+/// `scrn-tree 2 <key>`, then one
+/// `<depth>\t<r|d>\t<marker>\t<name>\t<label>\t<#rrggbb>\t<path>` line per
+/// node, depth first. Only the label and colour of `.scrn.toml` are kept,
+/// so loading reads no repo files; the background scan re-reads them.
+pub fn save_cache(roots: &[WorkspaceRoot], opts: &ScanOptions, tree: &TreeNode) {
+    write_cache(&cache_path(), roots, opts, tree);
+}
+
+fn write_cache(file: &Path, roots: &[WorkspaceRoot], opts: &ScanOptions, tree: &TreeNode) {
+    fn write(node: &TreeNode, depth: usize, out: &mut String) -> bool {
+        let path = node.path.to_string_lossy();
+        let cfg = node.repo_config.as_ref();
+        let label = cfg.and_then(|c| c.label.as_deref()).unwrap_or("");
+        if [node.name.as_str(), label, &path].iter().any(|f| f.contains(['\t', '\n'])) {
+            return false;
+        }
+        let kind = if node.is_repo { 'r' } else { 'd' };
+        let marker = node.marker.as_deref().unwrap_or("");
+        let color = cfg
+            .and_then(|c| c.color)
+            .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"))
+            .unwrap_or_default();
+        out.push_str(&format!("{depth}\t{kind}\t{marker}\t{}\t{label}\t{color}\t{path}\n", node.name));
+        node.children.iter().all(|c| write(c, depth + 1, out))
+    }
+    let mut out = format!("scrn-tree 2 {}\n", cache_key(roots, opts));
+    // A name the format can't hold means no cache, not a wrong one
+    if !write(tree, 0, &mut out) {
+        return;
+    }
+    let _ = statefile::write_atomic(file, &out);
+}
+
+/// The cached tree, if it was scanned with the same roots and options.
+pub fn load_cache(roots: &[WorkspaceRoot], opts: &ScanOptions) -> Option<TreeNode> {
+    read_cache(&cache_path(), roots, opts)
+}
+
+fn read_cache(file: &Path, roots: &[WorkspaceRoot], opts: &ScanOptions) -> Option<TreeNode> {
+    let contents = fs::read_to_string(file).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != format!("scrn-tree 2 {}", cache_key(roots, opts)) {
+        return None;
+    }
+    // Nodes still being filled in, one per depth
+    let mut stack: Vec<TreeNode> = Vec::new();
+    for line in lines {
+        let mut fields = line.splitn(7, '\t');
+        let depth: usize = fields.next()?.parse().ok()?;
+        let is_repo = fields.next()? == "r";
+        let marker = Some(fields.next()?.to_string()).filter(|m| !m.is_empty());
+        let name = fields.next()?.to_string();
+        let label = Some(fields.next()?.to_string()).filter(|l| !l.is_empty());
+        let color = config::parse_color(fields.next()?);
+        let path = PathBuf::from(fields.next()?);
+        if depth > stack.len() || (depth == 0 && !stack.is_empty()) {
+            return None;
+        }
+        while stack.len() > depth {
+            let done = stack.pop()?;
+            stack.last_mut()?.children.push(done);
+        }
+        let repo_config = (label.is_some() || color.is_some()).then(|| RepoConfig { label, color, ..Default::default() });
+        stack.push(TreeNode { name, path, is_repo, children: Vec::new(), repo_config, marker });
+    }
+    while stack.len() > 1 {
+        let done = stack.pop()?;
+        stack.last_mut()?.children.push(done);
+    }
+    stack.pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> IgnoreRules {
+        let mut rules = IgnoreRules { rules: Vec::new() };
+        for line in lines {
+            rules.push(line);
+        }
+        rules
+    }
+
+    fn node(name: &str, path: &str, is_repo: bool, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode { name: name.into(), path: path.into(), is_repo, children, repo_config: None, marker: None }
+    }
+
+    fn cache_file(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("scrn-test-{}-{test}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("tree")
+    }
+
+    fn sample_tree() -> TreeNode {
+        let mut api = node("api", "/w/api", true, Vec::new());
+        api.marker = Some(".git".into());
+        api.repo_config = Some(RepoConfig { label: Some("API".into()), color: Some((255, 0, 16)), ..Default::default() });
+        let lib = node("lib", "/w/libs/lib", true, Vec::new());
+        node("", "/w", false, vec![api, node("libs", "/w/libs", false, vec![lib])])
+    }
+
+    #[test]
+    fn cache_round_trips() {
+        let file = cache_file("round-trip");
+        let roots = [WorkspaceRoot { name: "w".into(), path: "/w".into() }];
+        let opts = ScanOptions::default();
+        let tree = sample_tree();
+        write_cache(&file, &roots, &opts, &tree);
+        assert_eq!(read_cache(&file, &roots, &opts), Some(tree));
+        let _ = fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn cache_for_other_roots_or_options_is_ignored() {
+        let file = cache_file("stale-key");
+        let roots = [WorkspaceRoot { name: "w".into(), path: "/w".into() }];
+        let opts = ScanOptions::default();
+        write_cache(&file, &roots, &opts, &sample_tree());
+        let moved = [WorkspaceRoot { name: "w".into(), path: "/elsewhere".into() }];
+        assert_eq!(read_cache(&file, &moved, &opts), None);
+        let deeper = ScanOptions { max_depth: Some(1), ..ScanOptions::default() };
+        assert_eq!(read_cache(&file, &roots, &deeper), None);
+        let _ = fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn corrupt_cache_is_ignored() {
+        let file = cache_file("corrupt");
+        let roots = [WorkspaceRoot { name: "w".into(), path: "/w".into() }];
+        let opts = ScanOptions::default();
+        let header = format!("scrn-tree 2 {}\n", cache_key(&roots, &opts));
+        for body in ["0\td\t\t\t\t\t/w\n2\tr\t\tapi\t\t\t/w/api\n", "0\td\t/w\n", "x\td\t\t\t\t\t/w\n"] {
+            fs::write(&file, format!("{header}{body}")).unwrap();
+            assert_eq!(read_cache(&file, &roots, &opts), None, "{body:?}");
+        }
+        fs::write(&file, "scrn-tree 1\n").unwrap();
+        assert_eq!(read_cache(&file, &roots, &opts), None);
+        let _ = fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn unanchored_pattern_matches_at_any_depth() {
+        let rules = rules(&["target"]);
+        assert!(rules.is_ignored("target"));
+        assert!(rules.is_ignored("work/api/target"));
+        assert!(!rules.is_ignored("work/targets"));
+    }
+
+    #[test]
+    fn anchored_pattern_matches_only_at_the_root() {
+        let rules = rules(&["/build", "work/tmp"]);
+        assert!(rules.is_ignored("build"));
+        assert!(!rules.is_ignored("work/build"));
+        assert!(rules.is_ignored("work/tmp"));
+        assert!(!rules.is_ignored("other/work/tmp"));
+    }
+
+    #[test]
+    fn globstar_prunes_the_directory_itself() {
+        let rules = rules(&["vendor/**", "# comment", ""]);
+        assert!(rules.is_ignored("vendor"));
+        assert!(rules.is_ignored("vendor/lib/x"));
+        assert_eq!(rules.rules.len(), 1);
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let keep = rules(&["archive-*", "!archive-keep"]);
+        assert!(keep.is_ignored("archive-old"));
+        assert!(!keep.is_ignored("archive-keep"));
+        let drop = rules(&["!archive-keep", "archive-*"]);
+        assert!(drop.is_ignored("archive-keep"));
+    }
+}